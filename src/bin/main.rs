@@ -3,41 +3,46 @@
 
 use defmt::*;
 use embassy_executor::Spawner;
-use embassy_stm32::dma::NoDma;
-use embassy_stm32::gpio::{Level, Output, Speed};
+use embassy_stm32::exti::ExtiInput;
+use embassy_stm32::gpio::{Input, Level, Output, Pull, Speed};
 use embassy_stm32::i2c::I2c;
 use embassy_stm32::time::Hertz;
 use embassy_stm32::{bind_interrupts, i2c, peripherals};
-use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
 use embassy_sync::mutex::Mutex;
 
 use embassy_time::{Duration, Timer};
 use {defmt_rtt as _, panic_probe as _};
 
-use sensor_pack::lis2mdl::Lis2mdl;
-use sensor_pack::lps22hh::Lps22hh;
+use sensor_pack::lis2mdl::{Lis2mdl, Lis2mdlConfig};
+use sensor_pack::lps22hh::{Lps22hh, Lps22hhConfig};
 use sensor_pack::lsm6dso::Lsm6dso;
+use sensor_pack::SharedBus;
+use sensor_pack::SlaveAddr;
 
 bind_interrupts!(struct Irqs {
     I2C1_EV => i2c::EventInterruptHandler<peripherals::I2C1>;
     I2C1_ER => i2c::ErrorInterruptHandler<peripherals::I2C1>;
 });
 
-type Channel1 =
-    Mutex<CriticalSectionRawMutex, Option<I2c<'static, peripherals::I2C1, NoDma, NoDma>>>;
+// I2C1 TX on DMA1 stream 6, RX on DMA1 stream 0 (channel 1)
+type I2c1 = I2c<'static, peripherals::I2C1, peripherals::DMA1_CH6, peripherals::DMA1_CH0>;
+type Channel1 = SharedBus<I2c1>;
 static I2C_REF: Channel1 = Mutex::new(None);
 
 #[embassy_executor::main]
 async fn main(_spawner: Spawner) {
     let p = embassy_stm32::init(Default::default());
     let mut led = Output::new(p.PB7, Level::Low, Speed::Low);
-    let i2c_ch: I2c<'static, peripherals::I2C1, NoDma, NoDma> = i2c::I2c::new(
+    // X-NUCLEO-IKS01A3 INT_DRDY_P on D10, DRDY_M on D9
+    let mut baro_drdy = ExtiInput::new(Input::new(p.PD14, Pull::None), p.EXTI14);
+    let mut mag_drdy = ExtiInput::new(Input::new(p.PD15, Pull::None), p.EXTI15);
+    let i2c_ch: I2c1 = i2c::I2c::new(
         p.I2C1,
         p.PB8,
         p.PB9,
         Irqs,
-        NoDma,
-        NoDma,
+        p.DMA1_CH6,
+        p.DMA1_CH0,
         Hertz(100_000),
         i2c::Config::default(),
     );
@@ -46,43 +51,58 @@ async fn main(_spawner: Spawner) {
         *(I2C_REF.lock().await) = Some(i2c_ch);
     }
 
-    let mut sensor = Lps22hh::new(&I2C_REF);
+    let mut sensor = Lps22hh::new(&I2C_REF, SlaveAddr::Default);
 
     let mut sensor2 = Lis2mdl::new(&I2C_REF);
 
-    //let mut sensor3 = Lsm6dso::new(&I2C_REF);
+    //let mut sensor3 = Lsm6dso::new(&I2C_REF, SlaveAddr::Default);
 
     info!("Blink");
     Timer::after(Duration::from_millis(1000)).await;
-    if sensor.check_device_id().await.unwrap() {
+    if sensor.check_device_id().await.is_ok() {
         info!("Polling Lps22hh");
         led.set_high();
     }
     Timer::after(Duration::from_millis(1000)).await;
-    sensor.apply_config().await.unwrap();
+    sensor
+        .apply_config(&Lps22hhConfig::default())
+        .await
+        .unwrap();
+    sensor.route_data_ready(true).await.unwrap();
     led.set_low();
 
     Timer::after(Duration::from_millis(1000)).await;
-    if sensor2.check_device_id().await.unwrap() {
+    if sensor2.check_device_id().await.is_ok() {
         info!("Polling Lis2mdl");
         led.set_high();
     }
     Timer::after(Duration::from_millis(1000)).await;
-    sensor2.apply_config().await.unwrap();
+    sensor2
+        .apply_config(&Lis2mdlConfig::default())
+        .await
+        .unwrap();
+    sensor2.route_data_ready(true).await.unwrap();
 
     //Timer::after(Duration::from_millis(1000)).await;
-    //if sensor3.check_device_id().await.unwrap() {
+    //if sensor3.check_device_id().await.is_ok() {
     //    info!("Polling Lsm6dso");
     //    led.set_high();
     //}
 
     loop {
-        Timer::after(Duration::from_millis(400)).await;
+        sensor.wait_for_data(&mut baro_drdy).await.unwrap();
         led.set_low();
-        sensor.sample().await.unwrap();
-        Timer::after(Duration::from_millis(10)).await;
+        let baro = sensor.sample().await.unwrap();
+        info!(
+            "Pressure: {} Pa, Temperature: {} C",
+            baro.pressure.0, baro.temperature.0
+        );
+        sensor2.wait_for_data(&mut mag_drdy).await.unwrap();
         led.set_high();
-        sensor2.sample().await.unwrap();
-        Timer::after(Duration::from_millis(400)).await;
+        let mag = sensor2.sample().await.unwrap();
+        info!(
+            "Mag Field: {}, {}, {} G, Temperature: {} C",
+            mag.magnetic_field.x, mag.magnetic_field.y, mag.magnetic_field.z, mag.temperature.0
+        );
     }
 }
@@ -3,39 +3,43 @@
 
 use defmt::*;
 use embassy_executor::Spawner;
-use embassy_stm32::dma::NoDma;
-use embassy_stm32::gpio::{Level, Output, Speed};
+use embassy_stm32::exti::ExtiInput;
+use embassy_stm32::gpio::{Input, Level, Output, Pull, Speed};
 use embassy_stm32::i2c::I2c;
 use embassy_stm32::time::Hertz;
 use embassy_stm32::{bind_interrupts, i2c, peripherals};
-use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
 use embassy_sync::mutex::Mutex;
 
 use embassy_time::{Duration, Timer};
 use {defmt_rtt as _, panic_probe as _};
 
-use sensor_pack::lsm6dso::Lsm6dso;
+use sensor_pack::lsm6dso::{InterruptPin, Lsm6dso, Lsm6dsoConfig};
+use sensor_pack::SharedBus;
+use sensor_pack::SlaveAddr;
 
 bind_interrupts!(struct Irqs {
     I2C1_EV => i2c::EventInterruptHandler<peripherals::I2C1>;
     I2C1_ER => i2c::ErrorInterruptHandler<peripherals::I2C1>;
 });
 
-type Channel1 =
-    Mutex<CriticalSectionRawMutex, Option<I2c<'static, peripherals::I2C1, NoDma, NoDma>>>;
+// I2C1 TX on DMA1 stream 6, RX on DMA1 stream 0 (channel 1)
+type I2c1 = I2c<'static, peripherals::I2C1, peripherals::DMA1_CH6, peripherals::DMA1_CH0>;
+type Channel1 = SharedBus<I2c1>;
 static I2C_REF: Channel1 = Mutex::new(None);
 
 #[embassy_executor::main]
 async fn main(_spawner: Spawner) {
     let p = embassy_stm32::init(Default::default());
     let mut led = Output::new(p.PB7, Level::Low, Speed::Low);
-    let i2c_ch: I2c<'static, peripherals::I2C1, NoDma, NoDma> = i2c::I2c::new(
+    // X-NUCLEO-IKS01A3 INT1_DSO on D4
+    let mut drdy = ExtiInput::new(Input::new(p.PF14, Pull::None), p.EXTI14);
+    let i2c_ch: I2c1 = i2c::I2c::new(
         p.I2C1,
         p.PB8,
         p.PB9,
         Irqs,
-        NoDma,
-        NoDma,
+        p.DMA1_CH6,
+        p.DMA1_CH0,
         Hertz(100_000),
         i2c::Config::default(),
     );
@@ -44,23 +48,38 @@ async fn main(_spawner: Spawner) {
         *(I2C_REF.lock().await) = Some(i2c_ch);
     }
 
-    let mut sensor = Lsm6dso::new(&I2C_REF);
+    let mut sensor = Lsm6dso::new(&I2C_REF, SlaveAddr::Default);
 
     info!("Blink");
     Timer::after(Duration::from_millis(1000)).await;
     info!("Polling Lsm6dso");
-    if sensor.check_device_id().await.unwrap() {
+    if sensor.check_device_id().await.is_ok() {
         led.set_high();
     }
-    //Timer::after(Duration::from_millis(1000)).await;
-    //sensor.apply_config().await.unwrap();
-    //led.set_low();
+    Timer::after(Duration::from_millis(1000)).await;
+    sensor
+        .apply_config(&Lsm6dsoConfig::default())
+        .await
+        .unwrap();
+    sensor
+        .route_data_ready(InterruptPin::Int1, true)
+        .await
+        .unwrap();
+    led.set_low();
 
-    //loop {
-    //    Timer::after(Duration::from_millis(400)).await;
-    //    led.set_low();
-    //    sensor.sample().await.unwrap();
-    //    Timer::after(Duration::from_millis(100)).await;
-    //    led.set_high();
-    //}
+    loop {
+        sensor.wait_for_data(&mut drdy).await.unwrap();
+        led.toggle();
+        let imu = sensor.sample().await.unwrap();
+        info!(
+            "Acc: {}, {}, {} g, Gyr: {}, {}, {} dps, Temperature: {} C",
+            imu.acceleration.x,
+            imu.acceleration.y,
+            imu.acceleration.z,
+            imu.angular_rate.x,
+            imu.angular_rate.y,
+            imu.angular_rate.z,
+            imu.temperature.0
+        );
+    }
 }
@@ -0,0 +1,69 @@
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::mutex::Mutex;
+use embedded_hal_async::i2c::{ErrorType, I2c, Operation, SevenBitAddress};
+
+/// Bus shared between the drivers, filled in by the application once the
+/// peripheral has been initialized
+pub type SharedBus<BUS> = Mutex<CriticalSectionRawMutex, Option<BUS>>;
+
+/// Adapter exposing a blocking `embedded_hal::i2c::I2c` bus through the
+/// `embedded_hal_async::i2c::I2c` trait used by the drivers
+///
+/// Each transfer runs synchronously when the returned future is first
+/// polled, blocking the executor until the transfer finishes.
+pub struct Blocking<T>(pub T);
+
+impl<T: ErrorType> ErrorType for Blocking<T> {
+    type Error = T::Error;
+}
+
+impl<T: embedded_hal::i2c::I2c> I2c for Blocking<T> {
+    async fn read(&mut self, address: SevenBitAddress, read: &mut [u8]) -> Result<(), T::Error> {
+        self.0.read(address, read)
+    }
+
+    async fn write(&mut self, address: SevenBitAddress, write: &[u8]) -> Result<(), T::Error> {
+        self.0.write(address, write)
+    }
+
+    async fn write_read(
+        &mut self,
+        address: SevenBitAddress,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), T::Error> {
+        self.0.write_read(address, write, read)
+    }
+
+    async fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), T::Error> {
+        self.0.transaction(address, operations)
+    }
+}
+
+/// I2C slave address selection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SlaveAddr {
+    /// Default address, SA0/SDO pin high as on the X-NUCLEO-IKS01A3
+    #[default]
+    Default,
+    /// Alternate address, SA0/SDO pin low
+    Alternate,
+    /// Raw 7-bit address
+    Raw(u8),
+}
+
+impl SlaveAddr {
+    /// 7-bit address given the device's default and alternate addresses
+    pub(crate) fn addr(self, default: u8, alternate: u8) -> u8 {
+        match self {
+            SlaveAddr::Default => default,
+            SlaveAddr::Alternate => alternate,
+            SlaveAddr::Raw(address) => address,
+        }
+    }
+}
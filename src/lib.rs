@@ -1,7 +1,15 @@
-#![no_std]
-#![no_main]
+#![cfg_attr(not(test), no_std)]
 
+#[macro_use]
+mod fmt;
+
+pub mod bus;
 pub mod lis2mdl;
 pub mod lps22hh;
 pub mod lsm6dso;
+pub mod measurement;
+mod register;
+pub mod transport;
 
+pub use bus::{SharedBus, SlaveAddr};
+pub use transport::{I2cTransport, SpiTransport, Transport};
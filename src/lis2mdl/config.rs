@@ -0,0 +1,123 @@
+use super::reg::{self, ModeOp, Odr};
+
+/// LIS2MDL configuration
+///
+/// The default configuration runs the magnetometer continuously at 100 Hz
+/// with temperature compensation and block data update enabled.
+#[derive(Copy, Clone)]
+pub struct Lis2mdlConfig {
+    odr: Odr,
+    mode: ModeOp,
+    temperature_compensation: bool,
+    low_power: bool,
+    low_pass_filter: bool,
+    offset_cancellation: bool,
+    block_data_update: bool,
+}
+
+impl Default for Lis2mdlConfig {
+    fn default() -> Self {
+        Self {
+            odr: Odr::Hz100,
+            mode: ModeOp::Continuous,
+            temperature_compensation: true,
+            low_power: false,
+            low_pass_filter: false,
+            offset_cancellation: false,
+            block_data_update: true,
+        }
+    }
+}
+
+impl Lis2mdlConfig {
+    /// CFG_REG_A bits owned by the configuration
+    pub(crate) const CFG_REG_A_MASK: u8 =
+        reg::COMP_TEMP_EN | reg::LP | reg::ODR_MASK | reg::MOD_MASK;
+    /// CFG_REG_B bits owned by the configuration
+    pub(crate) const CFG_REG_B_MASK: u8 = reg::LPF | reg::OFF_CANC;
+    /// CFG_REG_C bits owned by the configuration
+    pub(crate) const CFG_REG_C_MASK: u8 = reg::BDU;
+
+    /// Output data rate
+    #[must_use]
+    pub fn odr(mut self, odr: Odr) -> Self {
+        self.odr = odr;
+        self
+    }
+
+    /// Mode of operation
+    #[must_use]
+    pub fn mode(mut self, mode: ModeOp) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Magnetometer temperature compensation
+    #[must_use]
+    pub fn temperature_compensation(mut self, enable: bool) -> Self {
+        self.temperature_compensation = enable;
+        self
+    }
+
+    /// Low-power mode instead of high-resolution mode
+    #[must_use]
+    pub fn low_power(mut self, enable: bool) -> Self {
+        self.low_power = enable;
+        self
+    }
+
+    /// Digital low-pass filter, bandwidth ODR/4 instead of ODR/2
+    #[must_use]
+    pub fn low_pass_filter(mut self, enable: bool) -> Self {
+        self.low_pass_filter = enable;
+        self
+    }
+
+    /// Offset cancellation
+    #[must_use]
+    pub fn offset_cancellation(mut self, enable: bool) -> Self {
+        self.offset_cancellation = enable;
+        self
+    }
+
+    /// Block data update, output registers are not updated until both
+    /// bytes of a sample have been read
+    #[must_use]
+    pub fn block_data_update(mut self, enable: bool) -> Self {
+        self.block_data_update = enable;
+        self
+    }
+
+    /// CFG_REG_A (60h) value
+    pub fn cfg_reg_a(&self) -> u8 {
+        let mut reg = self.odr as u8 | self.mode as u8;
+        if self.temperature_compensation {
+            reg |= reg::COMP_TEMP_EN;
+        }
+        if self.low_power {
+            reg |= reg::LP;
+        }
+        reg
+    }
+
+    /// CFG_REG_B (61h) value
+    pub fn cfg_reg_b(&self) -> u8 {
+        let mut reg = 0;
+        if self.low_pass_filter {
+            reg |= reg::LPF;
+        }
+        if self.offset_cancellation {
+            reg |= reg::OFF_CANC;
+        }
+        reg
+    }
+
+    /// CFG_REG_C (62h) value
+    pub fn cfg_reg_c(&self) -> u8 {
+        if self.block_data_update {
+            reg::BDU
+        } else {
+            0
+        }
+    }
+}
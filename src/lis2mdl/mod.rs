@@ -1,132 +1,260 @@
-use core::fmt::Error;
-use defmt::*;
-use embassy_stm32::{dma::NoDma, i2c::I2c, peripherals};
-use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
-use embassy_sync::mutex::Mutex;
-mod reg;
-use self::reg::COMP_TEMP_EN;
-pub use reg::Register;
-
-type Channel1 =
-    Mutex<CriticalSectionRawMutex, Option<I2c<'static, peripherals::I2C1, NoDma, NoDma>>>;
-
-pub struct Lis2mdl {
-    i2c: &'static Channel1,
+use crate::measurement::{MagneticField, Temperature};
+use crate::register::RegisterBus;
+use crate::transport::{I2cTransport, SpiTransport, Transport};
+use crate::SharedBus;
+use embedded_hal::digital::Error as _;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::I2c;
+use embedded_hal_async::spi::SpiDevice;
+mod config;
+pub(crate) mod reg;
+pub use config::Lis2mdlConfig;
+pub use reg::{ModeOp, Odr, Register};
+
+/// LIS2MDL driver error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error<E> {
+    /// Bus error
+    Bus(E),
+    /// Data-ready pin error
+    Pin(embedded_hal::digital::ErrorKind),
+    /// The shared bus has not been initialized yet
+    BusNotInitialized,
+    /// WHO_AM_I returned an unexpected value
+    WrongDeviceId(u8),
+    /// A configuration register did not read back the value written to it
+    ConfigMismatch {
+        register: Register,
+        written: u8,
+        read: u8,
+    },
+    /// No new magnetic field data is available
+    DataNotReady,
 }
 
-impl Lis2mdl {
-    #[must_use]
-    pub fn new(i2c: &'static Channel1) -> Self {
-        Self { i2c }
-    }
+/// Raw output register counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RawSample {
+    pub magnetic_field: [i16; 3],
+    pub temperature: i16,
+}
+
+/// Magnetic field and temperature reading
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Sample {
+    pub magnetic_field: MagneticField,
+    pub temperature: Temperature,
+}
 
-    pub async fn check_device_id(&self) -> Result<bool, Error> {
-        let mut data = [0u8, 1];
-        
-        let mut i2c_unlocked = self.i2c.lock().await;
-        let i2c_mut = i2c_unlocked.as_mut().ok_or(Error).unwrap();
-
-        match i2c_mut.blocking_write_read(reg::I2C_SAD, &[Register::WHO_AM_I as u8], &mut data) {
-            Ok(()) => {
-                info!("Whoami: {}", data[0]);
-                if data[0] == reg::DEVICE_ID {
-                    Ok(true)
-                } else {
-                    Ok(false)
-                }
-            }
-            Err(e) => {
-                error!("I2C Error: {:?}", e);
-                Err(Error)
-            }
+impl From<RawSample> for Sample {
+    fn from(raw: RawSample) -> Self {
+        let [x, y, z] = raw
+            .magnetic_field
+            .map(|v| reg::GAUSS_PER_LSB * f32::from(v));
+        Self {
+            magnetic_field: MagneticField { x, y, z },
+            temperature: Temperature(reg::DEG_PER_LSB * f32::from(raw.temperature) + reg::REF_TEMP),
         }
     }
+}
+
+pub struct Lis2mdl<T: Transport> {
+    bus: &'static SharedBus<T::Bus>,
+    transport: T,
+}
 
-    pub async fn apply_config(&self) -> Result<bool, Error> {
-        let mut reg: u8 = 0;
-
-        // === CFG_REG_A (60h) ===
-        reg |= COMP_TEMP_EN;
-        reg |= reg::Odr::Hz100 as u8;
-        reg |= reg::ModeOp::Continuous as u8;
-        info!("comp_temp: {}", COMP_TEMP_EN);
-        info!("Odr: {}", reg::Odr::Hz100 as u8);
-        info!("ModeOp: {}", reg::ModeOp::Continuous as u8);
-        info!("CFG_REG_A to write: {}", reg);
-
-        let mut data = [0u8, 1];
-
-        let mut i2c_unlocked = self.i2c.lock().await;
-
-        let i2c_mut = i2c_unlocked.as_mut().ok_or(Error).unwrap(); // Lock the inner I2c instance
-        match i2c_mut.blocking_write_read(reg::I2C_SAD, &[Register::CFG_REG_A as u8], &mut data) {
-            Ok(()) => {
-                info!("CFG_REG_A_original: {}", data[0]);
-            }
-            Err(e) => {
-                error!("I2C Error: {:?}", e);
-            }
+impl<I2C: I2c + 'static> Lis2mdl<I2cTransport<I2C>> {
+    /// The LIS2MDL has a single fixed I2C address
+    #[must_use]
+    pub fn new(i2c: &'static SharedBus<I2C>) -> Self {
+        Self {
+            bus: i2c,
+            transport: I2cTransport::new(reg::I2C_SAD),
         }
-        i2c_mut
-            .blocking_write(reg::I2C_SAD, &[Register::CFG_REG_A as u8, reg])
-            .unwrap();
-        match i2c_mut.blocking_write_read(reg::I2C_SAD, &[Register::CFG_REG_A as u8], &mut data) {
-            Ok(()) => {
-                info!("CFG_REG_A_written: {}", data[0]);
-                Ok(true)
-            }
-            Err(e) => {
-                error!("I2C Error: {:?}", e);
-                Err(Error)
-            }
+    }
+}
+
+impl<SPI: SpiDevice + 'static> Lis2mdl<SpiTransport<SPI>> {
+    /// The LIS2MDL powers up in 3-wire SPI mode, call
+    /// [`enable_4wire_spi`](Self::enable_4wire_spi) before reading anything
+    /// when SDO is wired to a separate MISO line
+    #[must_use]
+    pub fn new_spi(spi: &'static SharedBus<SPI>) -> Self {
+        Self {
+            bus: spi,
+            transport: SpiTransport::new(),
         }
     }
 
-    pub async fn sample(&self) -> Result<bool, Error> {
-        let mut buffer = [0u8; 6];
+    /// Switch the SPI interface to 4-wire mode
+    ///
+    /// The register cannot be read back in 3-wire mode, so CFG_REG_C is
+    /// written blindly with its reset value plus the 4-wire bit. Call this
+    /// right after power-up, before applying a configuration.
+    pub async fn enable_4wire_spi(&self) -> Result<(), Error<SPI::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        regs.write_reg(Register::CFG_REG_C, reg::_4WSPI)
+            .await
+            .map_err(Error::Bus)
+    }
 
-        let mut i2c_unlocked = self.i2c.lock().await;
+    /// Disable the I2C interface, so that traffic to other devices on lines
+    /// shared with the SPI bus cannot be mistaken for I2C transfers
+    pub async fn disable_i2c(&self) -> Result<(), Error<SPI::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
 
-        let i2c_mut = i2c_unlocked.as_mut().ok_or(Error).unwrap(); // Lock the inner I2c instance
+        update_verified(&mut regs, Register::CFG_REG_C, reg::I2C_DIS, reg::I2C_DIS).await
+    }
+}
 
-        match i2c_mut.blocking_write_read(reg::I2C_SAD, &[Register::OUT_X_L as u8], &mut buffer) {
-            Ok(()) => {
-                let out_x = (buffer[1] as i16) << 8 | buffer[0] as i16;
-                let out_y = (buffer[3] as i16) << 8 | buffer[2] as i16;
-                let out_z = (buffer[5] as i16) << 8 | buffer[4] as i16;
+impl<T: Transport> Lis2mdl<T> {
+    pub async fn check_device_id(&self) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
 
-                let magnetic_field_x = reg::GAUSS_PER_LSB * f32::from(out_x);
-                let magnetic_field_y = reg::GAUSS_PER_LSB * f32::from(out_y);
-                let magnetic_field_z = reg::GAUSS_PER_LSB * f32::from(out_z);
+        let id = regs
+            .read_reg(Register::WHO_AM_I)
+            .await
+            .map_err(Error::Bus)?;
+        info!("Whoami: {}", id);
 
-                info!(
-                    "Mag Field: {}, {}, {}",
-                    magnetic_field_x, magnetic_field_y, magnetic_field_z
-                );
-            }
-            Err(e) => {
-                error!("I2C Error: {:?}", e);
-            }
+        if id == reg::DEVICE_ID {
+            Ok(())
+        } else {
+            Err(Error::WrongDeviceId(id))
         }
+    }
+
+    /// Write the configuration fields and verify them by read-back, bits not
+    /// covered by the configuration are left untouched
+    pub async fn apply_config(&self, config: &Lis2mdlConfig) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        update_verified(
+            &mut regs,
+            Register::CFG_REG_A,
+            Lis2mdlConfig::CFG_REG_A_MASK,
+            config.cfg_reg_a(),
+        )
+        .await?;
+        update_verified(
+            &mut regs,
+            Register::CFG_REG_B,
+            Lis2mdlConfig::CFG_REG_B_MASK,
+            config.cfg_reg_b(),
+        )
+        .await?;
+        update_verified(
+            &mut regs,
+            Register::CFG_REG_C,
+            Lis2mdlConfig::CFG_REG_C_MASK,
+            config.cfg_reg_c(),
+        )
+        .await
+    }
+
+    /// Change the output data rate only
+    pub async fn set_odr(&self, odr: Odr) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        update_verified(&mut regs, Register::CFG_REG_A, reg::ODR_MASK, odr as u8).await
+    }
+
+    /// Change the mode of operation only
+    pub async fn set_mode(&self, mode: ModeOp) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
 
-        let mut temp = [0u8; 2];
-        match i2c_mut.blocking_write_read(
-            reg::I2C_SAD,
-            &[Register::TEMP_OUT_L_REG as u8],
-            &mut temp,
-        ) {
-            Ok(()) => {
-                let out_x = (temp[1] as i16) << 8 | temp[0] as i16;
-
-                let temperature = reg::DEG_PER_LSB * f32::from(out_x) + reg::REF_TEMP;
-
-                info!("Temperature: {}", temperature);
-                Ok(true)
-            }
-            Err(e) => {
-                error!("I2C Error: {:?}", e);
-                Err(Error)
-            }
+        update_verified(&mut regs, Register::CFG_REG_A, reg::MOD_MASK, mode as u8).await
+    }
+
+    /// Route the data-ready signal to the DRDY pin. The signal is active high
+    /// and stays asserted until the output registers are read.
+    pub async fn route_data_ready(&self, enable: bool) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let value = if enable { reg::DRDY_ON_PIN } else { 0 };
+        update_verified(&mut regs, Register::CFG_REG_C, reg::DRDY_ON_PIN, value).await
+    }
+
+    /// Wait until the data-ready signal routed by
+    /// [`route_data_ready`](Self::route_data_ready) is asserted on `pin`
+    pub async fn wait_for_data(&self, pin: &mut impl Wait) -> Result<(), Error<T::Error>> {
+        pin.wait_for_high().await.map_err(|e| Error::Pin(e.kind()))
+    }
+
+    /// Read the magnetic field and temperature output registers in one burst
+    pub async fn sample_raw(&self) -> Result<RawSample, Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let status = regs
+            .read_reg(Register::STATUS_REG)
+            .await
+            .map_err(Error::Bus)?;
+        if status & reg::ZYXDA == 0 {
+            return Err(Error::DataNotReady);
         }
+
+        // OUT_X_L (68h) .. TEMP_OUT_H_REG (6Fh)
+        let mut buffer = [0u8; 8];
+        regs.read_regs(Register::OUT_X_L, &mut buffer)
+            .await
+            .map_err(Error::Bus)?;
+
+        Ok(RawSample {
+            magnetic_field: [
+                i16::from_le_bytes([buffer[0], buffer[1]]),
+                i16::from_le_bytes([buffer[2], buffer[3]]),
+                i16::from_le_bytes([buffer[4], buffer[5]]),
+            ],
+            temperature: i16::from_le_bytes([buffer[6], buffer[7]]),
+        })
+    }
+
+    /// Read the magnetic field and temperature
+    pub async fn sample(&self) -> Result<Sample, Error<T::Error>> {
+        self.sample_raw().await.map(Sample::from)
+    }
+}
+
+/// Update the bits of `register` selected by `mask` and verify them by read-back
+async fn update_verified<T: Transport>(
+    regs: &mut RegisterBus<'_, T>,
+    register: Register,
+    mask: u8,
+    value: u8,
+) -> Result<(), Error<T::Error>> {
+    let written = regs
+        .modify_reg(register, mask, value)
+        .await
+        .map_err(Error::Bus)?;
+    let read = regs.read_reg(register).await.map_err(Error::Bus)?;
+
+    if read == written {
+        Ok(())
+    } else {
+        Err(Error::ConfigMismatch {
+            register,
+            written,
+            read,
+        })
     }
 }
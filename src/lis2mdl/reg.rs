@@ -1,5 +1,6 @@
 #![allow(non_upper_case_globals)]
 #![allow(dead_code)]
+use crate::register::RegisterAddress;
 #[cfg(feature = "out_f32")]
 use cast::f32;
 #[cfg(feature = "out_f32")]
@@ -24,7 +25,8 @@ pub enum Mode {
 #[allow(dead_code)]
 #[allow(non_camel_case_types)]
 #[allow(clippy::upper_case_acronyms)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Register {
     OFFSET_X_REG_L = 0x45,
     OFFSET_X_REG_H = 0x46,
@@ -59,6 +61,12 @@ impl Register {
     }
 }
 
+impl RegisterAddress for Register {
+    fn addr(self) -> u8 {
+        Register::addr(self)
+    }
+}
+
 // === WHO_AM_I (4Fh) ===
 
 /// WHO_AM_I device identification register
@@ -0,0 +1,80 @@
+use super::reg::{self, Odr, LPF};
+
+/// LPS22HH configuration
+///
+/// The default configuration samples at 50 Hz in low-noise mode with the
+/// low-pass filter at ODR/9 and block data update enabled.
+#[derive(Copy, Clone)]
+pub struct Lps22hhConfig {
+    odr: Odr,
+    lpf: LPF,
+    low_noise: bool,
+    block_data_update: bool,
+}
+
+impl Default for Lps22hhConfig {
+    fn default() -> Self {
+        Self {
+            odr: Odr::Hz50,
+            lpf: LPF::DIV_9,
+            low_noise: true,
+            block_data_update: true,
+        }
+    }
+}
+
+impl Lps22hhConfig {
+    /// CTRL_REG1 bits owned by the configuration
+    pub(crate) const CTRL_REG1_MASK: u8 = reg::ODR_MASK | reg::LPF_MASK | reg::BDU;
+    /// CTRL_REG2 bits owned by the configuration
+    pub(crate) const CTRL_REG2_MASK: u8 = reg::IF_ADD_INC | reg::LOW_NOISE_EN;
+
+    /// Output data rate
+    #[must_use]
+    pub fn odr(mut self, odr: Odr) -> Self {
+        self.odr = odr;
+        self
+    }
+
+    /// Pressure low-pass filter bandwidth
+    #[must_use]
+    pub fn lpf(mut self, lpf: LPF) -> Self {
+        self.lpf = lpf;
+        self
+    }
+
+    /// Low-noise mode instead of low-current mode. Not available at 200 Hz,
+    /// where low-current mode is used regardless.
+    #[must_use]
+    pub fn low_noise(mut self, enable: bool) -> Self {
+        self.low_noise = enable;
+        self
+    }
+
+    /// Block data update, output registers are not updated until the whole
+    /// sample has been read
+    #[must_use]
+    pub fn block_data_update(mut self, enable: bool) -> Self {
+        self.block_data_update = enable;
+        self
+    }
+
+    /// CTRL_REG1 (10h) value
+    pub fn ctrl_reg1(&self) -> u8 {
+        let mut reg = self.odr as u8 | self.lpf as u8;
+        if self.block_data_update {
+            reg |= reg::BDU;
+        }
+        reg
+    }
+
+    /// CTRL_REG2 (11h) value, register auto-increment is always enabled and
+    /// low-noise mode is left off at 200 Hz
+    pub fn ctrl_reg2(&self) -> u8 {
+        let mut reg = reg::IF_ADD_INC;
+        if self.low_noise && !matches!(self.odr, Odr::Hz200) {
+            reg |= reg::LOW_NOISE_EN;
+        }
+        reg
+    }
+}
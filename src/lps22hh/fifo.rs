@@ -0,0 +1,180 @@
+use super::reg::{self, FifoMode};
+use super::{update_verified, Error, Lps22hh, RawSample, Register, Sample};
+use crate::register::RegisterBus;
+use crate::transport::Transport;
+
+/// FIFO samples read per bus transaction when draining the FIFO
+const BURST_SAMPLES: usize = 32;
+
+/// LPS22HH FIFO configuration
+///
+/// The default configuration runs the FIFO in continuous mode with no
+/// watermark.
+#[derive(Copy, Clone)]
+pub struct FifoConfig {
+    mode: FifoMode,
+    watermark: u8,
+    stop_on_watermark: bool,
+}
+
+impl Default for FifoConfig {
+    fn default() -> Self {
+        Self {
+            mode: FifoMode::Continuous,
+            watermark: 0,
+            stop_on_watermark: false,
+        }
+    }
+}
+
+impl FifoConfig {
+    /// FIFO_CTRL bits owned by the configuration
+    pub(crate) const FIFO_CTRL_MASK: u8 = reg::STOP_ON_WTM | reg::F_MODE_MASK;
+
+    /// FIFO mode
+    #[must_use]
+    pub fn mode(mut self, mode: FifoMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Watermark in samples, saturated at 127
+    #[must_use]
+    pub fn watermark(mut self, samples: u8) -> Self {
+        self.watermark = samples.min(reg::WTM_MASK);
+        self
+    }
+
+    /// Limit the FIFO depth to the watermark
+    #[must_use]
+    pub fn stop_on_watermark(mut self, enable: bool) -> Self {
+        self.stop_on_watermark = enable;
+        self
+    }
+
+    /// FIFO_CTRL (13h) value
+    pub fn fifo_ctrl(&self) -> u8 {
+        let mut reg = self.mode as u8;
+        if self.stop_on_watermark {
+            reg |= reg::STOP_ON_WTM;
+        }
+        reg
+    }
+
+    /// FIFO_WTM (14h) value
+    pub fn fifo_wtm(&self) -> u8 {
+        self.watermark
+    }
+}
+
+/// FIFO fill level and flags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FifoStatus {
+    /// Unread samples
+    pub level: u8,
+    /// The level reached the watermark
+    pub watermark: bool,
+    /// Samples have been overwritten
+    pub overrun: bool,
+    /// The FIFO is full
+    pub full: bool,
+}
+
+impl FifoStatus {
+    fn from_regs(status: [u8; 2]) -> Self {
+        Self {
+            level: status[0],
+            watermark: status[1] & reg::FIFO_WTM_IA != 0,
+            overrun: status[1] & reg::FIFO_OVR_IA != 0,
+            full: status[1] & reg::FIFO_FULL_IA != 0,
+        }
+    }
+}
+
+impl<T: Transport> Lps22hh<T> {
+    /// Write the FIFO configuration and verify it by read-back
+    pub async fn configure_fifo(&self, config: &FifoConfig) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        update_verified(
+            &mut regs,
+            Register::FIFO_WTM,
+            reg::WTM_MASK,
+            config.fifo_wtm(),
+        )
+        .await?;
+        update_verified(
+            &mut regs,
+            Register::FIFO_CTRL,
+            FifoConfig::FIFO_CTRL_MASK,
+            config.fifo_ctrl(),
+        )
+        .await
+    }
+
+    /// Change the FIFO mode only, switching to bypass empties the FIFO
+    pub async fn set_fifo_mode(&self, mode: FifoMode) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        update_verified(&mut regs, Register::FIFO_CTRL, reg::F_MODE_MASK, mode as u8).await
+    }
+
+    /// Route the FIFO watermark flag to the INT_DRDY pin
+    pub async fn route_fifo_watermark(&self, enable: bool) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let value = if enable { reg::INT_F_WTM } else { 0 };
+        update_verified(&mut regs, Register::CTRL_REG3, reg::INT_F_WTM, value).await
+    }
+
+    /// Read the FIFO fill level and flags
+    pub async fn fifo_status(&self) -> Result<FifoStatus, Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let mut status = [0u8; 2];
+        regs.read_regs(Register::FIFO_STATUS1, &mut status)
+            .await
+            .map_err(Error::Bus)?;
+        Ok(FifoStatus::from_regs(status))
+    }
+
+    /// Drain up to `samples.len()` samples from the FIFO, returning the
+    /// number of samples written
+    pub async fn read_fifo(&self, samples: &mut [Sample]) -> Result<usize, Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let mut status = [0u8; 2];
+        regs.read_regs(Register::FIFO_STATUS1, &mut status)
+            .await
+            .map_err(Error::Bus)?;
+        let count = samples
+            .len()
+            .min(FifoStatus::from_regs(status).level.into())
+            .min(reg::FIFO_DEPTH);
+
+        // The read address wraps from FIFO_DATA_OUT_T_H (7Ch) back to
+        // FIFO_DATA_OUT_P_XL (78h), so consecutive samples are read in one burst
+        let mut buffer = [[0u8; reg::SAMPLE_LEN]; BURST_SAMPLES];
+        for chunk in samples[..count].chunks_mut(BURST_SAMPLES) {
+            let raw = &mut buffer[..chunk.len()];
+            regs.read_regs(Register::FIFO_DATA_OUT_P_XL, raw.as_flattened_mut())
+                .await
+                .map_err(Error::Bus)?;
+            for (sample, bytes) in chunk.iter_mut().zip(raw.iter()) {
+                *sample = RawSample::from_le_bytes(*bytes).into();
+            }
+        }
+        Ok(count)
+    }
+}
@@ -1,155 +1,247 @@
-use core::fmt::Error;
-use defmt::*;
-use embassy_stm32::{dma::NoDma, i2c::I2c, peripherals};
+use crate::measurement::{Pressure, Temperature};
+use crate::register::RegisterBus;
+use crate::transport::{I2cTransport, SpiTransport, Transport};
+use crate::{SharedBus, SlaveAddr};
+use embedded_hal::digital::Error as _;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::I2c;
+use embedded_hal_async::spi::SpiDevice;
+mod config;
+mod fifo;
 mod reg;
-use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
-use embassy_sync::mutex::Mutex;
-pub use reg::Register;
+pub use config::Lps22hhConfig;
+pub use fifo::{FifoConfig, FifoStatus};
+pub use reg::{FifoMode, Odr, Register, LPF};
+
+/// LPS22HH driver error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error<E> {
+    /// Bus error
+    Bus(E),
+    /// Data-ready pin error
+    Pin(embedded_hal::digital::ErrorKind),
+    /// The shared bus has not been initialized yet
+    BusNotInitialized,
+    /// WHO_AM_I returned an unexpected value
+    WrongDeviceId(u8),
+    /// A configuration register did not read back the value written to it
+    ConfigMismatch {
+        register: Register,
+        written: u8,
+        read: u8,
+    },
+    /// No new pressure data is available
+    DataNotReady,
+}
 
-type Channel1 =
-    Mutex<CriticalSectionRawMutex, Option<I2c<'static, peripherals::I2C1, NoDma, NoDma>>>;
+/// Raw output register counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RawSample {
+    pub pressure: i32,
+    pub temperature: i16,
+}
 
-pub struct Lps22hh {
-    i2c: &'static Channel1,
+/// Pressure and temperature reading
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Sample {
+    pub pressure: Pressure,
+    pub temperature: Temperature,
 }
 
-impl Lps22hh {
-    #[must_use]
-    pub fn new(i2c: &'static Channel1) -> Self {
-        Self { i2c }
+impl RawSample {
+    /// Decode the pressure and temperature output registers
+    fn from_le_bytes(bytes: [u8; reg::SAMPLE_LEN]) -> Self {
+        Self {
+            // 24-bit two's complement, sign extended
+            pressure: i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8,
+            temperature: i16::from_le_bytes([bytes[3], bytes[4]]),
+        }
     }
+}
 
-    pub async fn check_device_id(&self) -> Result<bool, Error> {
-        let mut data = [0u8, 1];
-        let mut i2c_unlocked = self.i2c.lock().await;
-
-        let i2c_mut = i2c_unlocked.as_mut().ok_or(Error).unwrap(); // Lock the inner I2c instance
-
-        match i2c_mut.blocking_write_read(reg::I2C_SAD, &[Register::WHO_AM_I as u8], &mut data) {
-            Ok(()) => {
-                info!("Whoami: {}", data[0]);
-                if data[0] == reg::DEVICE_ID {
-                    Ok(true)
-                } else {
-                    Ok(false)
-                }
-            }
-            Err(e) => {
-                error!("I2C Error: {:?}", e);
-                Err(Error)
-            }
+impl From<RawSample> for Sample {
+    fn from(raw: RawSample) -> Self {
+        Self {
+            pressure: Pressure(raw.pressure as f32 / reg::LSB_PER_hPA * 100.0),
+            temperature: Temperature(f32::from(raw.temperature) * reg::DEG_PER_LSB),
         }
     }
+}
 
-    pub async fn apply_config(&self) -> Result<bool, Error> {
-        let mut reg1: u8 = 0;
+pub struct Lps22hh<T: Transport> {
+    bus: &'static SharedBus<T::Bus>,
+    transport: T,
+}
 
-        // === CTRL_REG1 (10h) ===
-        reg1 |= reg::Odr::Hz50 as u8;
-        reg1 |= reg::LPF::DIV_9 as u8;
-        info!("CTRL_REG1 to write: {}", reg1);
+impl<I2C: I2c + 'static> Lps22hh<I2cTransport<I2C>> {
+    #[must_use]
+    pub fn new(i2c: &'static SharedBus<I2C>, address: SlaveAddr) -> Self {
+        Self {
+            bus: i2c,
+            transport: I2cTransport::new(address.addr(reg::I2C_SAD, reg::I2C_SAD_ALT)),
+        }
+    }
+}
 
-        let mut data = [0u8, 1];
-        let mut i2c_unlocked = self.i2c.lock().await;
+impl<SPI: SpiDevice + 'static> Lps22hh<SpiTransport<SPI>> {
+    /// 4-wire SPI, the power-up default
+    #[must_use]
+    pub fn new_spi(spi: &'static SharedBus<SPI>) -> Self {
+        Self {
+            bus: spi,
+            transport: SpiTransport::new(),
+        }
+    }
 
-        //let i2c_guard: &I2c<'static, peripherals::I2C1, NoDma, NoDma> = i2c_unlocked.as_ref().ok_or(Error)?;
-        let i2c_mut = i2c_unlocked.as_mut().ok_or(Error).unwrap(); // Lock the inner I2c instance
+    /// Disable the I2C interface, so that traffic to other devices on lines
+    /// shared with the SPI bus cannot be mistaken for I2C transfers
+    pub async fn disable_i2c(&self) -> Result<(), Error<SPI::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        update_verified(
+            &mut regs,
+            Register::IF_CTRL,
+            reg::I2C_DISABLE,
+            reg::I2C_DISABLE,
+        )
+        .await
+    }
+}
 
-        match i2c_mut.blocking_write_read(reg::I2C_SAD, &[Register::CTRL_REG1 as u8], &mut data) {
-            Ok(()) => {
-                info!("CTRL_REG1_original: {}", data[0]);
-            }
-            Err(e) => {
-                error!("I2C Error: {:?}", e);
-            }
+impl<T: Transport> Lps22hh<T> {
+    pub async fn check_device_id(&self) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let id = regs
+            .read_reg(Register::WHO_AM_I)
+            .await
+            .map_err(Error::Bus)?;
+        info!("Whoami: {}", id);
+
+        if id == reg::DEVICE_ID {
+            Ok(())
+        } else {
+            Err(Error::WrongDeviceId(id))
         }
+    }
 
-        i2c_mut
-            .blocking_write(reg::I2C_SAD, &[Register::CTRL_REG1 as u8, reg1])
-            .unwrap();
-
-        match i2c_mut.blocking_write_read(reg::I2C_SAD, &[Register::CTRL_REG1 as u8], &mut data) {
-            Ok(()) => {
-                info!("CTRL_REG1_written: {}", data[0]);
-            }
-            Err(e) => {
-                error!("I2C Error: {:?}", e);
-            }
-        }
+    /// Write the configuration fields and verify them by read-back, bits not
+    /// covered by the configuration are left untouched
+    pub async fn apply_config(&self, config: &Lps22hhConfig) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        update_verified(
+            &mut regs,
+            Register::CTRL_REG2,
+            Lps22hhConfig::CTRL_REG2_MASK,
+            config.ctrl_reg2(),
+        )
+        .await?;
+        update_verified(
+            &mut regs,
+            Register::CTRL_REG1,
+            Lps22hhConfig::CTRL_REG1_MASK,
+            config.ctrl_reg1(),
+        )
+        .await
+    }
 
-        let mut reg2: u8 = 0;
-
-        // === CTRL_REG2 (10h) ===
-        reg2 |= reg::IF_ADD_INC;
-        reg2 |= reg::LOW_NOISE_EN;
-        info!("CTRL_REG2 to write: {}", reg2);
-
-        let mut data = [0u8, 1];
-        match i2c_mut.blocking_write_read(reg::I2C_SAD, &[Register::CTRL_REG2 as u8], &mut data) {
-            Ok(()) => {
-                info!("CTRL_REG2_original: {}", data[0]);
-            }
-            Err(e) => {
-                error!("I2C Error: {:?}", e);
-            }
-        }
-        i2c_mut
-            .blocking_write(reg::I2C_SAD, &[Register::CTRL_REG2 as u8, reg2])
-            .unwrap();
-        match i2c_mut.blocking_write_read(reg::I2C_SAD, &[Register::CTRL_REG2 as u8], &mut data) {
-            Ok(()) => {
-                info!("CTRL_REG2_written: {}", data[0]);
-                Ok(true)
-            }
-            Err(e) => {
-                error!("I2C Error: {:?}", e);
-                Err(Error)
-            }
+    /// Change the output data rate only. Low-noise mode is not available at
+    /// 200 Hz and is switched off first; it stays off at lower rates until
+    /// the configuration is applied again.
+    pub async fn set_odr(&self, odr: Odr) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        if matches!(odr, Odr::Hz200) {
+            update_verified(&mut regs, Register::CTRL_REG2, reg::LOW_NOISE_EN, 0).await?;
         }
+        update_verified(&mut regs, Register::CTRL_REG1, reg::ODR_MASK, odr as u8).await
     }
 
-    pub async fn sample(&self) -> Result<bool, Error> {
-        let mut buffer = [0u8; 3];
+    /// Change the low-pass filter bandwidth only
+    pub async fn set_lpf(&self, lpf: LPF) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        update_verified(&mut regs, Register::CTRL_REG1, reg::LPF_MASK, lpf as u8).await
+    }
 
-        let mut i2c_unlocked = self.i2c.lock().await;
+    /// Route the data-ready signal to the INT_DRDY pin. The signal is active
+    /// high unless INT_H_L is set, and stays asserted until the output
+    /// registers are read.
+    pub async fn route_data_ready(&self, enable: bool) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
 
-        //let i2c_guard: &I2c<'static, peripherals::I2C1, NoDma, NoDma> = i2c_unlocked.as_ref().ok_or(Error)?;
-        let i2c_mut = i2c_unlocked.as_mut().ok_or(Error).unwrap(); // Lock the inner I2c instance
+        let value = if enable { reg::DRDY } else { 0 };
+        update_verified(&mut regs, Register::CTRL_REG3, reg::DRDY, value).await
+    }
 
-        match i2c_mut.blocking_write_read(
-            reg::I2C_SAD,
-            &[Register::PRESSURE_OUT_XL as u8],
-            &mut buffer,
-        ) {
-            Ok(()) => {
-                let press_buf =
-                    (buffer[2] as i32) << 16 | (buffer[1] as i32) << 8 | buffer[0] as i32;
+    /// Wait until the data-ready signal routed by
+    /// [`route_data_ready`](Self::route_data_ready) is asserted on `pin`
+    pub async fn wait_for_data(&self, pin: &mut impl Wait) -> Result<(), Error<T::Error>> {
+        pin.wait_for_high().await.map_err(|e| Error::Pin(e.kind()))
+    }
 
-                let press = f64::from(press_buf) / (reg::LSB_PER_hPA as f64) * 100.0;
+    /// Read the pressure and temperature output registers in one burst
+    pub async fn sample_raw(&self) -> Result<RawSample, Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
 
-                info!("Pressure: {}", press);
-            }
-            Err(e) => {
-                error!("I2C Error: {:?}", e);
-            }
+        let status = regs.read_reg(Register::STATUS).await.map_err(Error::Bus)?;
+        if status & reg::P_DA == 0 {
+            return Err(Error::DataNotReady);
         }
 
-        let mut buffer = [0u8; 2];
+        // PRESSURE_OUT_XL (28h) .. TEMP_OUT_H (2Ch)
+        let mut buffer = [0u8; reg::SAMPLE_LEN];
+        regs.read_regs(Register::PRESSURE_OUT_XL, &mut buffer)
+            .await
+            .map_err(Error::Bus)?;
 
-        match i2c_mut.blocking_write_read(reg::I2C_SAD, &[Register::TEMP_OUT_L as u8], &mut buffer)
-        {
-            Ok(()) => {
-                let temp_buf = (buffer[1] as i16) << 8 | buffer[0] as i16;
+        Ok(RawSample::from_le_bytes(buffer))
+    }
 
-                let temp = f32::from(temp_buf) * (reg::DEG_PER_LSB as f32);
+    /// Read the pressure and temperature
+    pub async fn sample(&self) -> Result<Sample, Error<T::Error>> {
+        self.sample_raw().await.map(Sample::from)
+    }
+}
 
-                info!("Temperature: {}", temp);
-                Ok(true)
-            }
-            Err(e) => {
-                error!("I2C Error: {:?}", e);
-                Err(Error)
-            }
-        }
+/// Update the bits of `register` selected by `mask` and verify them by read-back
+async fn update_verified<T: Transport>(
+    regs: &mut RegisterBus<'_, T>,
+    register: Register,
+    mask: u8,
+    value: u8,
+) -> Result<(), Error<T::Error>> {
+    let written = regs
+        .modify_reg(register, mask, value)
+        .await
+        .map_err(Error::Bus)?;
+    let read = regs.read_reg(register).await.map_err(Error::Bus)?;
+
+    if read == written {
+        Ok(())
+    } else {
+        Err(Error::ConfigMismatch {
+            register,
+            written,
+            read,
+        })
     }
 }
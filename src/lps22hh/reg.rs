@@ -1,5 +1,6 @@
 #![allow(non_upper_case_globals)]
 #![allow(dead_code)]
+use crate::register::RegisterAddress;
 #[cfg(feature = "out_f32")]
 use cast::f32;
 #[cfg(feature = "out_f32")]
@@ -7,6 +8,8 @@ use num_derive::FromPrimitive;
 
 /// I2C slave address
 pub const I2C_SAD: u8 = 0b1011101;
+/// I2C slave address with SA0 low
+pub const I2C_SAD_ALT: u8 = 0b1011100;
 
 pub const LSB_PER_hPA: f32 = 4096.0;
 pub const DEG_PER_LSB: f32 = 0.01;
@@ -15,7 +18,8 @@ pub const REF_TEMP: f32 = 25.0;
 /// Register mapping
 #[allow(non_camel_case_types)]
 #[allow(clippy::upper_case_acronyms)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Register {
     INTERRUPT_CFG = 0x0B,
     THS_P_L = 0x0C,
@@ -55,6 +59,12 @@ impl Register {
     }
 }
 
+impl RegisterAddress for Register {
+    fn addr(self) -> u8 {
+        Register::addr(self)
+    }
+}
+
 // === WHO_AM_I (0Fh) ===
 
 /// WHO_AM_I device identification register
@@ -70,6 +80,8 @@ pub const I3C_DISABLE: u8 = 0b0000_0010;
 pub const I2C_DISABLE: u8 = 0b0000_0001;
 
 // === CTRL_REG1 (10h) ===
+pub const ODR_MASK: u8 = 0b0111_0000;
+pub const LPF_MASK: u8 = 0b0000_1100;
 pub const BDU: u8 = 0b0000_0010;
 pub const SIM: u8 = 0b0000_0001;
 
@@ -128,6 +140,8 @@ pub const INT_S0: u8 = 0b0000_0001;
 
 // === FIFO_CTRL (13h) ===
 pub const STOP_ON_WTM: u8 = 0b0000_1000;
+pub const TRIG_MODES: u8 = 0b0000_0100;
+pub const F_MODE_MASK: u8 = 0b0000_0111;
 // Fifo Mode
 #[derive(Copy, Clone)]
 #[allow(non_camel_case_types)]
@@ -147,3 +161,21 @@ pub enum FifoMode {
     ContToFifo = 0b0000_0111,
 }
 
+// === FIFO_WTM (14h) ===
+pub const WTM_MASK: u8 = 0b0111_1111;
+
+/// FIFO depth in pressure and temperature samples
+pub const FIFO_DEPTH: usize = 128;
+/// Bytes per output or FIFO sample, pressure followed by temperature
+pub const SAMPLE_LEN: usize = 5;
+
+// === FIFO_STATUS2 (26h) ===
+pub const FIFO_WTM_IA: u8 = 0b1000_0000;
+pub const FIFO_OVR_IA: u8 = 0b0100_0000;
+pub const FIFO_FULL_IA: u8 = 0b0010_0000;
+
+// === STATUS (27h) ===
+pub const T_OR: u8 = 0b0010_0000;
+pub const P_OR: u8 = 0b0001_0000;
+pub const T_DA: u8 = 0b0000_0010;
+pub const P_DA: u8 = 0b0000_0001;
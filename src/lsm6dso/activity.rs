@@ -0,0 +1,335 @@
+use super::reg::{self, AccScale, FreeFallThreshold, InactivityMode};
+use super::{update_verified, wait_for_event, Axis, Error, InterruptPin, Lsm6dso, Register};
+use crate::register::RegisterBus;
+use crate::transport::Transport;
+use embedded_hal_async::digital::Wait;
+
+/// LSM6DSO free-fall configuration
+///
+/// A free fall is detected when all axes stay below the threshold for the
+/// duration. The default configuration uses 312 mg for 6 ODR periods.
+#[derive(Copy, Clone)]
+pub struct FreeFallConfig {
+    threshold: FreeFallThreshold,
+    duration: u8,
+}
+
+impl Default for FreeFallConfig {
+    fn default() -> Self {
+        Self {
+            threshold: FreeFallThreshold::Mg312,
+            duration: 6,
+        }
+    }
+}
+
+impl FreeFallConfig {
+    /// Free-fall threshold
+    #[must_use]
+    pub fn threshold(mut self, threshold: FreeFallThreshold) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Minimum duration in accelerometer ODR periods, saturated at 63
+    #[must_use]
+    pub fn duration(mut self, periods: u8) -> Self {
+        self.duration = periods.min(reg::FF_DUR_MAX);
+        self
+    }
+
+    /// FREE_FALL (5Dh) value
+    pub fn free_fall(&self) -> u8 {
+        // FF_DUR[4:0], FF_DUR5 is in WAKE_UP_DUR
+        (self.duration & reg::FF_DUR_MAX >> 1) << reg::FF_DUR_SHIFT | self.threshold as u8
+    }
+
+    /// WAKE_UP_DUR (5Ch) FF_DUR5 bit
+    pub fn wake_up_dur(&self) -> u8 {
+        if self.duration > reg::FF_DUR_MAX >> 1 {
+            reg::FF_DUR5
+        } else {
+            0
+        }
+    }
+}
+
+/// LSM6DSO wake-up and activity/inactivity configuration
+///
+/// A wake-up is detected when the high-pass filtered acceleration on any
+/// axis exceeds the threshold for the duration. With inactivity enabled,
+/// the device falls asleep once no wake-up was detected for the sleep
+/// duration, and wakes up again on the next one. The default configuration
+/// detects wake-ups above 0.1 g with inactivity disabled.
+#[derive(Copy, Clone)]
+pub struct WakeUpConfig {
+    threshold: f32,
+    duration: u8,
+    sleep_duration: u8,
+    inactivity: InactivityMode,
+}
+
+impl Default for WakeUpConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 0.1,
+            duration: 0,
+            sleep_duration: 0,
+            inactivity: InactivityMode::Disabled,
+        }
+    }
+}
+
+impl WakeUpConfig {
+    /// WAKE_UP_DUR bits owned by the configuration
+    pub(crate) const WAKE_UP_DUR_MASK: u8 = !reg::FF_DUR5;
+
+    /// Wake-up threshold in g. The resolution is 1/256 of the accelerometer
+    /// full scale up to a quarter of it, and 1/64 above.
+    #[must_use]
+    pub fn threshold(mut self, g: f32) -> Self {
+        self.threshold = g;
+        self
+    }
+
+    /// Minimum duration above the threshold in accelerometer ODR periods,
+    /// saturated at 3
+    #[must_use]
+    pub fn duration(mut self, periods: u8) -> Self {
+        self.duration = periods.min(reg::WAKE_DUR_MAX);
+        self
+    }
+
+    /// Time without wake-up before falling asleep, 512 accelerometer ODR
+    /// periods per step or 16 when zero, saturated at 15
+    #[must_use]
+    pub fn sleep_duration(mut self, steps: u8) -> Self {
+        self.sleep_duration = steps.min(reg::SLEEP_DUR_MASK);
+        self
+    }
+
+    /// Power state while asleep, enables the activity/inactivity function
+    #[must_use]
+    pub fn inactivity(mut self, mode: InactivityMode) -> Self {
+        self.inactivity = mode;
+        self
+    }
+
+    /// Threshold in LSBs of the given accelerometer full scale, and whether
+    /// the fine weight is used
+    fn threshold_lsb(&self, scale: AccScale) -> (u8, bool) {
+        let fine = self.threshold / scale.full_scale_g() * reg::WK_THS_FINE_STEPS + 0.5;
+        if fine < f32::from(reg::WK_THS_MASK) + 1.0 {
+            return (fine as u8, true);
+        }
+        let coarse = self.threshold / scale.full_scale_g() * reg::WK_THS_STEPS + 0.5;
+        ((coarse as u8).min(reg::WK_THS_MASK), false)
+    }
+
+    /// WAKE_UP_THS (5Bh) WK_THS bits for the given accelerometer full scale
+    pub fn wake_up_ths(&self, scale: AccScale) -> u8 {
+        self.threshold_lsb(scale).0
+    }
+
+    /// WAKE_UP_DUR (5Ch) value for the given accelerometer full scale,
+    /// without FF_DUR5
+    pub fn wake_up_dur(&self, scale: AccScale) -> u8 {
+        let mut reg = self.duration << reg::WAKE_DUR_SHIFT | self.sleep_duration;
+        if self.threshold_lsb(scale).1 {
+            reg |= reg::WAKE_THS_W;
+        }
+        reg
+    }
+
+    /// TAP_CFG2 (58h) INACT_EN bits
+    pub fn tap_cfg2(&self) -> u8 {
+        self.inactivity as u8
+    }
+}
+
+/// Free-fall, wake-up and activity event decoded from WAKE_UP_SRC
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MotionEvent {
+    /// Free fall detected
+    pub free_fall: bool,
+    /// Wake-up detected, on the first of X, Y and Z that exceeded the
+    /// threshold
+    pub wake_up: Option<Axis>,
+    /// The device fell asleep or woke up, see `sleeping`
+    pub sleep_change: bool,
+    /// The device is in the inactive state
+    pub sleeping: bool,
+}
+
+impl MotionEvent {
+    /// Decode a WAKE_UP_SRC value, `None` when no event was detected
+    pub fn from_src(src: u8) -> Option<Self> {
+        if src & (reg::FF_SRC | reg::WU_SRC | reg::SLEEP_CHANGE_SRC) == 0 {
+            return None;
+        }
+        let wake_up = if src & reg::WU_SRC == 0 {
+            None
+        } else if src & reg::X_WU != 0 {
+            Some(Axis::X)
+        } else if src & reg::Y_WU != 0 {
+            Some(Axis::Y)
+        } else if src & reg::Z_WU != 0 {
+            Some(Axis::Z)
+        } else {
+            None
+        };
+        Some(Self {
+            free_fall: src & reg::FF_SRC != 0,
+            wake_up,
+            sleep_change: src & reg::SLEEP_CHANGE_SRC != 0,
+            sleeping: src & reg::SLEEP_STATE != 0,
+        })
+    }
+}
+
+/// Stream of free-fall, wake-up and activity events signalled on an
+/// interrupt pin, see [`Lsm6dso::motion_events`]
+pub struct MotionEvents<'a, T: Transport, P> {
+    sensor: &'a Lsm6dso<T>,
+    pin: &'a mut P,
+}
+
+impl<T: Transport, P: Wait> MotionEvents<'_, T, P> {
+    /// Wait for the next event. Pin assertions without an event in
+    /// WAKE_UP_SRC are skipped, so the pin should only carry these events.
+    pub async fn next(&mut self) -> Result<MotionEvent, Error<T::Error>> {
+        let sensor = self.sensor;
+        wait_for_event(self.pin, async || sensor.read_motion_event().await).await
+    }
+}
+
+impl<T: Transport> Lsm6dso<T> {
+    /// Write the free-fall configuration and verify it by read-back
+    pub async fn configure_free_fall(
+        &self,
+        config: &FreeFallConfig,
+    ) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        update_verified(&mut regs, Register::FREE_FALL, 0xFF, config.free_fall()).await?;
+        update_verified(
+            &mut regs,
+            Register::WAKE_UP_DUR,
+            reg::FF_DUR5,
+            config.wake_up_dur(),
+        )
+        .await?;
+        update_verified(
+            &mut regs,
+            Register::TAP_CFG2,
+            reg::INTERRUPTS_ENABLE,
+            reg::INTERRUPTS_ENABLE,
+        )
+        .await
+    }
+
+    /// Write the wake-up and activity configuration and verify it by
+    /// read-back. The threshold is converted with the current accelerometer
+    /// full scale, so configure the accelerometer first.
+    pub async fn configure_wake_up(&self, config: &WakeUpConfig) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        update_verified(
+            &mut regs,
+            Register::WAKE_UP_THS,
+            reg::WK_THS_MASK,
+            config.wake_up_ths(self.acc_scale),
+        )
+        .await?;
+        update_verified(
+            &mut regs,
+            Register::WAKE_UP_DUR,
+            WakeUpConfig::WAKE_UP_DUR_MASK,
+            config.wake_up_dur(self.acc_scale),
+        )
+        .await?;
+        update_verified(
+            &mut regs,
+            Register::TAP_CFG2,
+            reg::INTERRUPTS_ENABLE | reg::INACT_EN_MASK,
+            reg::INTERRUPTS_ENABLE | config.tap_cfg2(),
+        )
+        .await
+    }
+
+    /// Latch tap, orientation, free-fall, wake-up and activity events until
+    /// their source register is read, which clears them at once instead of
+    /// at the end of the ODR period
+    pub async fn latch_interrupts(&self, enable: bool) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let mask = reg::LIR | reg::INT_CLR_ON_READ;
+        let value = if enable { mask } else { 0 };
+        update_verified(&mut regs, Register::TAP_CFG0, mask, value).await
+    }
+
+    /// Route the free-fall, wake-up and activity change events to `pin`
+    pub async fn route_motion(
+        &self,
+        pin: InterruptPin,
+        free_fall: bool,
+        wake_up: bool,
+        sleep_change: bool,
+    ) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let (register, ff_mask, wu_mask, sleep_mask) = match pin {
+            InterruptPin::Int1 => (
+                Register::MD1_CFG,
+                reg::INT1_FF,
+                reg::INT1_WU,
+                reg::INT1_SLEEP_CHANGE,
+            ),
+            InterruptPin::Int2 => (
+                Register::MD2_CFG,
+                reg::INT2_FF,
+                reg::INT2_WU,
+                reg::INT2_SLEEP_CHANGE,
+            ),
+        };
+        let mut value = 0;
+        if free_fall {
+            value |= ff_mask;
+        }
+        if wake_up {
+            value |= wu_mask;
+        }
+        if sleep_change {
+            value |= sleep_mask;
+        }
+        update_verified(&mut regs, register, ff_mask | wu_mask | sleep_mask, value).await
+    }
+
+    /// Read and decode WAKE_UP_SRC, clearing latched events
+    pub async fn read_motion_event(&self) -> Result<Option<MotionEvent>, Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let src = regs
+            .read_reg(Register::WAKE_UP_SRC)
+            .await
+            .map_err(Error::Bus)?;
+        Ok(MotionEvent::from_src(src))
+    }
+
+    /// Free-fall, wake-up and activity events signalled on `pin`, routed with
+    /// [`route_motion`](Self::route_motion)
+    pub fn motion_events<'a, P: Wait>(&'a self, pin: &'a mut P) -> MotionEvents<'a, T, P> {
+        MotionEvents { sensor: self, pin }
+    }
+}
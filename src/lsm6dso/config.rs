@@ -0,0 +1,100 @@
+use super::reg::{self, AccOdr, AccScale, GyrOdr, GyrScale, Rounding};
+
+/// LSM6DSO configuration
+///
+/// The default configuration runs both sensors at 104 Hz, ±4 g and
+/// ±2000 dps with block data update enabled.
+#[derive(Copy, Clone)]
+pub struct Lsm6dsoConfig {
+    pub(crate) acc_odr: AccOdr,
+    pub(crate) acc_scale: AccScale,
+    pub(crate) gyr_odr: GyrOdr,
+    pub(crate) gyr_scale: GyrScale,
+    block_data_update: bool,
+    rounding: Rounding,
+}
+
+impl Default for Lsm6dsoConfig {
+    fn default() -> Self {
+        Self {
+            acc_odr: AccOdr::Hz104,
+            acc_scale: AccScale::G4,
+            gyr_odr: GyrOdr::Hz104,
+            gyr_scale: GyrScale::Dps2000,
+            block_data_update: true,
+            rounding: Rounding::NoRounding,
+        }
+    }
+}
+
+impl Lsm6dsoConfig {
+    /// CTRL3_C bits owned by the configuration
+    pub(crate) const CTRL3_C_MASK: u8 = reg::BDU | reg::IF_INC;
+
+    /// Accelerometer output data rate
+    #[must_use]
+    pub fn acc_odr(mut self, odr: AccOdr) -> Self {
+        self.acc_odr = odr;
+        self
+    }
+
+    /// Accelerometer full scale
+    #[must_use]
+    pub fn acc_scale(mut self, scale: AccScale) -> Self {
+        self.acc_scale = scale;
+        self
+    }
+
+    /// Gyroscope output data rate
+    #[must_use]
+    pub fn gyr_odr(mut self, odr: GyrOdr) -> Self {
+        self.gyr_odr = odr;
+        self
+    }
+
+    /// Gyroscope full scale
+    #[must_use]
+    pub fn gyr_scale(mut self, scale: GyrScale) -> Self {
+        self.gyr_scale = scale;
+        self
+    }
+
+    /// Block data update, output registers are not updated until both
+    /// bytes of a sample have been read
+    #[must_use]
+    pub fn block_data_update(mut self, enable: bool) -> Self {
+        self.block_data_update = enable;
+        self
+    }
+
+    /// Circular burst-mode read from the output registers
+    #[must_use]
+    pub fn rounding(mut self, rounding: Rounding) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    /// CTRL1_XL (10h) value
+    pub fn ctrl1_xl(&self) -> u8 {
+        self.acc_odr as u8 | self.acc_scale as u8
+    }
+
+    /// CTRL2_G (11h) value
+    pub fn ctrl2_g(&self) -> u8 {
+        self.gyr_odr as u8 | self.gyr_scale as u8
+    }
+
+    /// CTRL3_C (12h) value, register auto-increment is always enabled
+    pub fn ctrl3_c(&self) -> u8 {
+        let mut reg = reg::IF_INC;
+        if self.block_data_update {
+            reg |= reg::BDU;
+        }
+        reg
+    }
+
+    /// CTRL5_C (14h) value
+    pub fn ctrl5_c(&self) -> u8 {
+        self.rounding as u8
+    }
+}
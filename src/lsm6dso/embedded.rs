@@ -0,0 +1,242 @@
+use super::reg::{self, EmbeddedRegister, MemoryBank};
+use super::{update_verified, wait_for_event, with_bank, Error, InterruptPin, Lsm6dso, Register};
+use crate::register::RegisterBus;
+use crate::transport::Transport;
+use embedded_hal_async::digital::Wait;
+
+/// Low-power function run by the LSM6DSO on the accelerometer data. The
+/// accelerometer ODR must be at least 26 Hz.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum EmbeddedFunction {
+    /// Step detector and counter
+    Pedometer,
+    /// Significant motion, a change of location such as walking
+    SignificantMotion,
+    /// Relative tilt, the direction of gravity changed by more than 35 degrees
+    Tilt,
+}
+
+impl EmbeddedFunction {
+    /// EMB_FUNC_EN_A (04h) enable bit
+    fn enable_bit(self) -> u8 {
+        match self {
+            EmbeddedFunction::Pedometer => reg::PEDO_EN,
+            EmbeddedFunction::SignificantMotion => reg::SIGN_MOTION_EN,
+            EmbeddedFunction::Tilt => reg::TILT_EN,
+        }
+    }
+
+    /// EMB_FUNC_INT1 (0Ah) and EMB_FUNC_INT2 (0Eh) routing bit
+    fn int_bit(self) -> u8 {
+        match self {
+            EmbeddedFunction::Pedometer => reg::INT_STEP_DETECTOR,
+            EmbeddedFunction::SignificantMotion => reg::INT_SIG_MOT,
+            EmbeddedFunction::Tilt => reg::INT_TILT,
+        }
+    }
+}
+
+/// Embedded function events decoded from EMB_FUNC_STATUS
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EmbeddedEvents {
+    /// Step detected
+    pub step: bool,
+    /// Significant motion detected
+    pub significant_motion: bool,
+    /// Relative tilt detected
+    pub tilt: bool,
+    /// FSM long counter reached its timeout
+    pub fsm_long_counter: bool,
+}
+
+impl EmbeddedEvents {
+    /// Decode an EMB_FUNC_STATUS value
+    pub fn from_status(status: u8) -> Self {
+        Self {
+            step: status & reg::IS_STEP_DET != 0,
+            significant_motion: status & reg::IS_SIGMOT != 0,
+            tilt: status & reg::IS_TILT != 0,
+            fsm_long_counter: status & reg::IS_FSM_LC != 0,
+        }
+    }
+
+    /// Whether any event was detected
+    pub fn any(&self) -> bool {
+        self.step || self.significant_motion || self.tilt || self.fsm_long_counter
+    }
+}
+
+/// Update the MD1_CFG/MD2_CFG embedded functions route of `pin` to match
+/// the per-function routes in the embedded functions bank
+pub(super) async fn update_emb_func_route<T: Transport>(
+    regs: &mut RegisterBus<'_, T>,
+    pin: InterruptPin,
+) -> Result<(), Error<T::Error>> {
+    let (first, register, mask) = match pin {
+        InterruptPin::Int1 => (
+            EmbeddedRegister::EMB_FUNC_INT1,
+            Register::MD1_CFG,
+            reg::INT1_EMB_FUNC,
+        ),
+        InterruptPin::Int2 => (
+            EmbeddedRegister::EMB_FUNC_INT2,
+            Register::MD2_CFG,
+            reg::INT2_EMB_FUNC,
+        ),
+    };
+    // EMB_FUNC_INTx, FSM_INTx_A and FSM_INTx_B
+    let mut routes = [0u8; 3];
+    with_bank(regs, MemoryBank::EmbeddedFunctions, async |regs| {
+        regs.read_regs(first, &mut routes).await.map_err(Error::Bus)
+    })
+    .await?;
+    let routed = routes.iter().any(|&r| r != 0);
+    update_verified(regs, register, mask, if routed { mask } else { 0 }).await
+}
+
+impl<T: Transport> Lsm6dso<T> {
+    /// Enable or disable an embedded function
+    pub async fn enable_embedded_function(
+        &self,
+        function: EmbeddedFunction,
+        enable: bool,
+    ) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let mask = function.enable_bit();
+        with_bank(&mut regs, MemoryBank::EmbeddedFunctions, async |regs| {
+            regs.modify_reg(
+                EmbeddedRegister::EMB_FUNC_EN_A,
+                mask,
+                if enable { mask } else { 0 },
+            )
+            .await
+            .map_err(Error::Bus)
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Route the events of an embedded function to `pin`
+    pub async fn route_embedded_function(
+        &self,
+        pin: InterruptPin,
+        function: EmbeddedFunction,
+        enable: bool,
+    ) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let register = match pin {
+            InterruptPin::Int1 => EmbeddedRegister::EMB_FUNC_INT1,
+            InterruptPin::Int2 => EmbeddedRegister::EMB_FUNC_INT2,
+        };
+        let mask = function.int_bit();
+        with_bank(&mut regs, MemoryBank::EmbeddedFunctions, async |regs| {
+            regs.modify_reg(register, mask, if enable { mask } else { 0 })
+                .await
+                .map_err(Error::Bus)
+        })
+        .await?;
+        update_emb_func_route(&mut regs, pin).await
+    }
+
+    /// Latch embedded function and FSM events until their status register is
+    /// read, which clears them at once instead of at the end of the ODR
+    /// period. Disabling leaves the clear on read of the basic interrupts
+    /// untouched.
+    pub async fn latch_embedded_interrupts(&self, enable: bool) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        with_bank(&mut regs, MemoryBank::EmbeddedFunctions, async |regs| {
+            regs.modify_reg(
+                EmbeddedRegister::PAGE_RW,
+                reg::EMB_FUNC_LIR,
+                if enable { reg::EMB_FUNC_LIR } else { 0 },
+            )
+            .await
+            .map_err(Error::Bus)
+        })
+        .await?;
+        if enable {
+            update_verified(
+                &mut regs,
+                Register::TAP_CFG0,
+                reg::INT_CLR_ON_READ,
+                reg::INT_CLR_ON_READ,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Read the step counter
+    pub async fn step_count(&self) -> Result<u16, Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let mut buffer = [0u8; 2];
+        with_bank(&mut regs, MemoryBank::EmbeddedFunctions, async |regs| {
+            regs.read_regs(EmbeddedRegister::STEP_COUNTER_L, &mut buffer)
+                .await
+                .map_err(Error::Bus)
+        })
+        .await?;
+        Ok(u16::from_le_bytes(buffer))
+    }
+
+    /// Reset the step counter to zero
+    pub async fn reset_step_count(&self) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        with_bank(&mut regs, MemoryBank::EmbeddedFunctions, async |regs| {
+            regs.modify_reg(
+                EmbeddedRegister::EMB_FUNC_SRC,
+                reg::PEDO_RST_STEP,
+                reg::PEDO_RST_STEP,
+            )
+            .await
+            .map_err(Error::Bus)
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Read the embedded function events from the main page copy of
+    /// EMB_FUNC_STATUS, clearing latched events
+    pub async fn embedded_events(&self) -> Result<EmbeddedEvents, Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let status = regs
+            .read_reg(Register::EMB_FUNC_STATUS_MAINPAGE)
+            .await
+            .map_err(Error::Bus)?;
+        Ok(EmbeddedEvents::from_status(status))
+    }
+
+    /// Wait for embedded function events signalled on `pin`, routed with
+    /// [`route_embedded_function`](Self::route_embedded_function). Pin
+    /// assertions without an event are skipped.
+    pub async fn wait_for_embedded_events(
+        &self,
+        pin: &mut impl Wait,
+    ) -> Result<EmbeddedEvents, Error<T::Error>> {
+        wait_for_event(pin, async || {
+            let events = self.embedded_events().await?;
+            Ok(events.any().then_some(events))
+        })
+        .await
+    }
+}
@@ -0,0 +1,324 @@
+use super::reg::{
+    self, AccBdr, AccScale, FifoMode, GyrBdr, GyrScale, TemperatureBdr, TimestampBatch,
+};
+use super::{update_verified, Error, InterruptPin, Lsm6dso, Register};
+use crate::measurement::{Acceleration, AngularRate, Temperature};
+use crate::register::RegisterBus;
+use crate::transport::Transport;
+
+/// FIFO words read per bus transaction when draining the FIFO
+const BURST_WORDS: usize = 16;
+
+/// LSM6DSO FIFO configuration
+///
+/// The default configuration batches both sensors at 104 Hz in continuous
+/// mode, without temperature or timestamp and with no watermark.
+#[derive(Copy, Clone)]
+pub struct FifoConfig {
+    mode: FifoMode,
+    acc_bdr: AccBdr,
+    gyr_bdr: GyrBdr,
+    temperature_bdr: TemperatureBdr,
+    timestamp: TimestampBatch,
+    watermark: u16,
+    stop_on_watermark: bool,
+}
+
+impl Default for FifoConfig {
+    fn default() -> Self {
+        Self {
+            mode: FifoMode::Continuous,
+            acc_bdr: AccBdr::Hz104,
+            gyr_bdr: GyrBdr::Hz104,
+            temperature_bdr: TemperatureBdr::NotBatched,
+            timestamp: TimestampBatch::NotBatched,
+            watermark: 0,
+            stop_on_watermark: false,
+        }
+    }
+}
+
+impl FifoConfig {
+    /// FIFO_CTRL2 bits owned by the configuration
+    pub(crate) const FIFO_CTRL2_MASK: u8 = reg::STOP_ON_WTM | reg::WTM8;
+    /// FIFO_CTRL4 bits owned by the configuration
+    pub(crate) const FIFO_CTRL4_MASK: u8 =
+        reg::DEC_TS_BATCH_MASK | reg::ODR_T_BATCH_MASK | reg::FIFO_MODE_MASK;
+
+    /// FIFO mode
+    #[must_use]
+    pub fn mode(mut self, mode: FifoMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Accelerometer batch data rate
+    #[must_use]
+    pub fn acc_bdr(mut self, bdr: AccBdr) -> Self {
+        self.acc_bdr = bdr;
+        self
+    }
+
+    /// Gyroscope batch data rate
+    #[must_use]
+    pub fn gyr_bdr(mut self, bdr: GyrBdr) -> Self {
+        self.gyr_bdr = bdr;
+        self
+    }
+
+    /// Temperature batch data rate
+    #[must_use]
+    pub fn temperature_bdr(mut self, bdr: TemperatureBdr) -> Self {
+        self.temperature_bdr = bdr;
+        self
+    }
+
+    /// Timestamp batching, enables the timestamp counter when batched
+    #[must_use]
+    pub fn timestamp(mut self, batch: TimestampBatch) -> Self {
+        self.timestamp = batch;
+        self
+    }
+
+    /// Watermark in FIFO words, saturated at 511
+    #[must_use]
+    pub fn watermark(mut self, words: u16) -> Self {
+        self.watermark = words.min(reg::WTM_MAX);
+        self
+    }
+
+    /// Stop batching once the watermark is reached
+    #[must_use]
+    pub fn stop_on_watermark(mut self, enable: bool) -> Self {
+        self.stop_on_watermark = enable;
+        self
+    }
+
+    /// FIFO_CTRL1 (07h) value
+    pub fn fifo_ctrl1(&self) -> u8 {
+        self.watermark as u8
+    }
+
+    /// FIFO_CTRL2 (08h) value
+    pub fn fifo_ctrl2(&self) -> u8 {
+        let mut reg = (self.watermark >> 8) as u8 & reg::WTM8;
+        if self.stop_on_watermark {
+            reg |= reg::STOP_ON_WTM;
+        }
+        reg
+    }
+
+    /// FIFO_CTRL3 (09h) value
+    pub fn fifo_ctrl3(&self) -> u8 {
+        self.gyr_bdr as u8 | self.acc_bdr as u8
+    }
+
+    /// FIFO_CTRL4 (0Ah) value
+    pub fn fifo_ctrl4(&self) -> u8 {
+        self.timestamp as u8 | self.temperature_bdr as u8 | self.mode as u8
+    }
+}
+
+/// FIFO fill level and flags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FifoStatus {
+    /// Unread words
+    pub level: u16,
+    /// The level reached the watermark
+    pub watermark: bool,
+    /// Words have been overwritten or dropped
+    pub overrun: bool,
+    /// The FIFO will be full at the next batch
+    pub full: bool,
+}
+
+impl FifoStatus {
+    fn from_regs(status: [u8; 2]) -> Self {
+        Self {
+            level: u16::from_le_bytes([status[0], status[1] & reg::DIFF_FIFO_H_MASK]),
+            watermark: status[1] & reg::FIFO_WTM_IA != 0,
+            overrun: status[1] & reg::FIFO_OVR_IA != 0,
+            full: status[1] & reg::FIFO_FULL_IA != 0,
+        }
+    }
+}
+
+/// Decoded FIFO word
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FifoSample {
+    Gyro(AngularRate),
+    Accel(Acceleration),
+    Temperature(Temperature),
+    /// Timestamp counter, 25 us per LSB
+    Timestamp(u32),
+    /// Bytes read by sensor hub slave 0 to 3, see
+    /// [`HubLis2mdl::decode`](super::HubLis2mdl::decode)
+    SensorHub {
+        slave: u8,
+        data: [u8; 6],
+    },
+    /// Word not decoded by the driver, such as a configuration change or
+    /// compressed data
+    Other {
+        tag: u8,
+        data: [u8; 6],
+    },
+}
+
+impl Default for FifoSample {
+    /// Empty slot for sample buffers
+    fn default() -> Self {
+        FifoSample::Other {
+            tag: 0,
+            data: [0; 6],
+        }
+    }
+}
+
+impl FifoSample {
+    /// Decode a FIFO word, axis data is scaled with the given full scales
+    pub fn decode(
+        word: [u8; reg::FIFO_WORD_LEN],
+        acc_scale: AccScale,
+        gyr_scale: GyrScale,
+    ) -> Self {
+        let tag = word[0] >> reg::TAG_SENSOR_SHIFT;
+        let mut data = [0u8; 6];
+        data.copy_from_slice(&word[1..]);
+        let axis = |i: usize| f32::from(i16::from_le_bytes([data[2 * i], data[2 * i + 1]]));
+
+        match tag {
+            reg::TAG_GYRO_NC => {
+                let [x, y, z] = [0, 1, 2].map(|i| axis(i) * gyr_scale.mdps_per_lsb() / 1000.0);
+                FifoSample::Gyro(AngularRate { x, y, z })
+            }
+            reg::TAG_ACCEL_NC => {
+                let [x, y, z] = [0, 1, 2].map(|i| axis(i) * acc_scale.mg_per_lsb() / 1000.0);
+                FifoSample::Accel(Acceleration { x, y, z })
+            }
+            reg::TAG_TEMPERATURE => {
+                FifoSample::Temperature(Temperature(axis(0) / reg::LSB_PER_DEG + reg::REF_TEMP))
+            }
+            reg::TAG_TIMESTAMP => {
+                FifoSample::Timestamp(u32::from_le_bytes([data[0], data[1], data[2], data[3]]))
+            }
+            reg::TAG_SENSORHUB_SLAVE0..=reg::TAG_SENSORHUB_SLAVE3 => FifoSample::SensorHub {
+                slave: tag - reg::TAG_SENSORHUB_SLAVE0,
+                data,
+            },
+            tag => FifoSample::Other { tag, data },
+        }
+    }
+}
+
+impl<T: Transport> Lsm6dso<T> {
+    /// Write the FIFO configuration and verify it by read-back
+    pub async fn configure_fifo(&self, config: &FifoConfig) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        update_verified(&mut regs, Register::FIFO_CTRL1, 0xFF, config.fifo_ctrl1()).await?;
+        update_verified(
+            &mut regs,
+            Register::FIFO_CTRL2,
+            FifoConfig::FIFO_CTRL2_MASK,
+            config.fifo_ctrl2(),
+        )
+        .await?;
+        update_verified(&mut regs, Register::FIFO_CTRL3, 0xFF, config.fifo_ctrl3()).await?;
+        if config.timestamp != TimestampBatch::NotBatched {
+            update_verified(
+                &mut regs,
+                Register::CTRL10_C,
+                reg::TIMESTAMP_EN,
+                reg::TIMESTAMP_EN,
+            )
+            .await?;
+        }
+        update_verified(
+            &mut regs,
+            Register::FIFO_CTRL4,
+            FifoConfig::FIFO_CTRL4_MASK,
+            config.fifo_ctrl4(),
+        )
+        .await
+    }
+
+    /// Change the FIFO mode only, switching to bypass empties the FIFO
+    pub async fn set_fifo_mode(&self, mode: FifoMode) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        update_verified(
+            &mut regs,
+            Register::FIFO_CTRL4,
+            reg::FIFO_MODE_MASK,
+            mode as u8,
+        )
+        .await
+    }
+
+    /// Route the FIFO watermark flag to `pin`
+    pub async fn route_fifo_watermark(
+        &self,
+        pin: InterruptPin,
+        enable: bool,
+    ) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let (register, mask) = match pin {
+            InterruptPin::Int1 => (Register::INT1_CTRL, reg::INT1_FIFO_TH),
+            InterruptPin::Int2 => (Register::INT2_CTRL, reg::INT2_FIFO_TH),
+        };
+        update_verified(&mut regs, register, mask, if enable { mask } else { 0 }).await
+    }
+
+    /// Read the FIFO fill level and flags
+    pub async fn fifo_status(&self) -> Result<FifoStatus, Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let mut status = [0u8; 2];
+        regs.read_regs(Register::FIFO_STATUS1, &mut status)
+            .await
+            .map_err(Error::Bus)?;
+        Ok(FifoStatus::from_regs(status))
+    }
+
+    /// Drain up to `samples.len()` words from the FIFO, returning the number
+    /// of samples written. Axis data is scaled with the current full scales.
+    pub async fn read_fifo(&self, samples: &mut [FifoSample]) -> Result<usize, Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let mut status = [0u8; 2];
+        regs.read_regs(Register::FIFO_STATUS1, &mut status)
+            .await
+            .map_err(Error::Bus)?;
+        let count = samples
+            .len()
+            .min(FifoStatus::from_regs(status).level.into());
+
+        // The read address wraps from FIFO_DATA_OUT_Z_H (7Eh) back to
+        // FIFO_DATA_OUT_TAG (78h), so consecutive words are read in one burst
+        let mut buffer = [[0u8; reg::FIFO_WORD_LEN]; BURST_WORDS];
+        for chunk in samples[..count].chunks_mut(BURST_WORDS) {
+            let words = &mut buffer[..chunk.len()];
+            regs.read_regs(Register::FIFO_DATA_OUT_TAG, words.as_flattened_mut())
+                .await
+                .map_err(Error::Bus)?;
+            for (sample, word) in chunk.iter_mut().zip(words.iter()) {
+                *sample = FifoSample::decode(*word, self.acc_scale, self.gyr_scale);
+            }
+        }
+        Ok(count)
+    }
+}
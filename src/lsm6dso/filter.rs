@@ -0,0 +1,214 @@
+use super::reg::{self, AccBandwidth, AccOdr, GyrOdr, GyroHpfCutoff, GyroLpfLevel};
+use super::{update_verified, Error, Lsm6dso, Register};
+use crate::register::RegisterBus;
+use crate::transport::Transport;
+
+/// Gyroscope LPF1 + LPF2 bandwidth in Hz for each FTYPE level, from 12.5 Hz
+/// to 6.66 kHz ODR, 0 where the level is not available
+#[rustfmt::skip]
+const GYRO_LPF1_HZ: [[f32; 10]; 8] = [
+    [4.2, 8.3, 16.6, 33.0, 67.0, 136.6, 239.2, 304.2, 328.5, 335.5],
+    [4.2, 8.3, 16.6, 33.0, 67.0, 130.5, 192.4, 220.7, 229.6, 232.0],
+    [4.2, 8.3, 16.6, 33.0, 67.0, 120.3, 154.2, 166.6, 170.1, 171.1],
+    [4.2, 8.3, 16.6, 33.0, 67.0, 137.1, 281.8, 453.2, 559.2, 609.0],
+    [4.2, 8.3, 16.7, 33.0, 62.4,  86.7,  96.6,  99.6,   0.0,   0.0],
+    [4.2, 8.3, 16.8, 31.0, 43.2,  48.0,  49.4,  49.8,   0.0,   0.0],
+    [4.1, 7.8, 13.4, 19.0, 23.1,  24.6,  25.0,  25.1,   0.0,   0.0],
+    [3.9, 6.7,  9.7, 11.5, 12.2,  12.4,  12.5,  12.5,   0.0,   0.0],
+];
+
+/// Highest ODR_G index, 208 Hz, at which the bandwidth does not depend on
+/// the LPF1 level
+const GYRO_LPF2_MAX_INDEX: usize = 4;
+
+/// Column of an ODR_G value in [`GYRO_LPF1_HZ`], `None` when powered down
+/// or at 1.6 Hz
+fn gyro_odr_index(bits: u8) -> Option<usize> {
+    match bits >> 4 {
+        n @ 1..=10 => Some(usize::from(n) - 1),
+        _ => None,
+    }
+}
+
+/// LSM6DSO accelerometer and gyroscope digital filter configuration
+///
+/// The default configuration is the power-up filter chain: accelerometer
+/// LPF1 only at ODR/2, gyroscope LPF1 and high-pass filter bypassed.
+#[derive(Copy, Clone, Default)]
+pub struct FilterConfig {
+    acc_bandwidth: Option<AccBandwidth>,
+    acc_hp_reference: bool,
+    gyr_lpf: Option<GyroLpfLevel>,
+    gyr_hpf: Option<GyroHpfCutoff>,
+}
+
+impl FilterConfig {
+    /// CTRL8_XL bits owned by the configuration
+    pub(crate) const CTRL8_XL_MASK: u8 =
+        reg::HPCF_XL_MASK | reg::HP_REF_MODE_XL | reg::HP_SLOPE_XL_EN;
+    /// CTRL7_G bits owned by the configuration
+    pub(crate) const CTRL7_G_MASK: u8 = reg::HP_EN_G | reg::HPM_G_MASK;
+
+    /// Accelerometer bandwidth, `None` for LPF1 only at ODR/2. The low-pass
+    /// bandwidths enable LPF2.
+    #[must_use]
+    pub fn acc_bandwidth(mut self, bandwidth: Option<AccBandwidth>) -> Self {
+        self.acc_bandwidth = bandwidth;
+        self
+    }
+
+    /// Accelerometer high-pass reference mode, only valid with a high-pass
+    /// bandwidth
+    #[must_use]
+    pub fn acc_hp_reference(mut self, enable: bool) -> Self {
+        self.acc_hp_reference = enable;
+        self
+    }
+
+    /// Gyroscope LPF1 level, `None` to bypass LPF1
+    #[must_use]
+    pub fn gyr_lpf(mut self, level: Option<GyroLpfLevel>) -> Self {
+        self.gyr_lpf = level;
+        self
+    }
+
+    /// Gyroscope high-pass filter cutoff, `None` to bypass the filter
+    #[must_use]
+    pub fn gyr_hpf(mut self, cutoff: Option<GyroHpfCutoff>) -> Self {
+        self.gyr_hpf = cutoff;
+        self
+    }
+
+    /// Whether the settings are consistent with each other and, with the
+    /// gyroscope running at `gyr_odr`, available at that ODR
+    pub fn is_valid(&self, gyr_odr: GyrOdr) -> bool {
+        self.is_valid_at(gyr_odr as u8)
+    }
+
+    /// [`is_valid`](Self::is_valid) for the ODR_G bits of CTRL2_G
+    fn is_valid_at(&self, odr_g: u8) -> bool {
+        let reference_valid =
+            !self.acc_hp_reference || self.acc_bandwidth.is_some_and(|b| b.is_high_pass());
+        let lpf_valid = match (self.gyr_lpf, gyro_odr_index(odr_g)) {
+            (Some(level), Some(index)) => GYRO_LPF1_HZ[level as usize][index] != 0.0,
+            _ => true,
+        };
+        reference_valid && lpf_valid
+    }
+
+    /// Accelerometer cutoff frequency in Hz at `odr`: the high-pass cutoff
+    /// for a high-pass bandwidth, the low-pass cutoff otherwise
+    pub fn acc_cutoff_hz(&self, odr: AccOdr) -> f32 {
+        match self.acc_bandwidth {
+            Some(bandwidth) => odr.hz() / bandwidth.odr_divider(),
+            None => odr.hz() / 2.0,
+        }
+    }
+
+    /// Gyroscope low-pass cutoff frequency in Hz at `odr`. `None` when
+    /// powered down, at 1.6 Hz, for an LPF1 level not available at `odr`,
+    /// and above 208 Hz with LPF1 bypassed, where the datasheet gives no
+    /// bandwidth.
+    pub fn gyr_lpf_cutoff_hz(&self, odr: GyrOdr) -> Option<f32> {
+        let index = gyro_odr_index(odr as u8)?;
+        let hz = match self.gyr_lpf {
+            Some(level) => GYRO_LPF1_HZ[level as usize][index],
+            None if index <= GYRO_LPF2_MAX_INDEX => GYRO_LPF1_HZ[0][index],
+            None => 0.0,
+        };
+        (hz != 0.0).then_some(hz)
+    }
+
+    /// Gyroscope high-pass cutoff frequency in Hz, `None` when bypassed
+    pub fn gyr_hpf_cutoff_hz(&self) -> Option<f32> {
+        self.gyr_hpf.map(GyroHpfCutoff::hz)
+    }
+
+    /// CTRL1_XL (10h) LPF2_XL_EN bit
+    pub fn ctrl1_xl(&self) -> u8 {
+        match self.acc_bandwidth {
+            Some(bandwidth) if !bandwidth.is_high_pass() => reg::LPF2_XL_EN,
+            _ => 0,
+        }
+    }
+
+    /// CTRL4_C (13h) LPF1_SEL_G bit
+    pub fn ctrl4_c(&self) -> u8 {
+        if self.gyr_lpf.is_some() {
+            reg::LPF1_SEL_G
+        } else {
+            0
+        }
+    }
+
+    /// CTRL6_C (15h) FTYPE bits
+    pub fn ctrl6_c(&self) -> u8 {
+        self.gyr_lpf.map_or(0, |level| level as u8)
+    }
+
+    /// CTRL7_G (16h) HP_EN_G and HPM_G bits
+    pub fn ctrl7_g(&self) -> u8 {
+        self.gyr_hpf.map_or(0, |cutoff| reg::HP_EN_G | cutoff as u8)
+    }
+
+    /// CTRL8_XL (17h) HPCF_XL, HP_REF_MODE_XL and HP_SLOPE_XL_EN bits
+    pub fn ctrl8_xl(&self) -> u8 {
+        let mut reg = self.acc_bandwidth.map_or(0, |bandwidth| bandwidth as u8);
+        if self.acc_hp_reference {
+            reg |= reg::HP_REF_MODE_XL;
+        }
+        reg
+    }
+}
+
+impl<T: Transport> Lsm6dso<T> {
+    /// Write the filter configuration and verify it by read-back. Fails with
+    /// [`Error::InvalidFilterConfig`] without writing anything when the
+    /// configuration is not valid at the current gyroscope ODR.
+    pub async fn configure_filters(&self, config: &FilterConfig) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let ctrl2_g = regs.read_reg(Register::CTRL2_G).await.map_err(Error::Bus)?;
+        if !config.is_valid_at(ctrl2_g & reg::ODR_G_MASK) {
+            return Err(Error::InvalidFilterConfig);
+        }
+
+        update_verified(
+            &mut regs,
+            Register::CTRL1_XL,
+            reg::LPF2_XL_EN,
+            config.ctrl1_xl(),
+        )
+        .await?;
+        update_verified(
+            &mut regs,
+            Register::CTRL8_XL,
+            FilterConfig::CTRL8_XL_MASK,
+            config.ctrl8_xl(),
+        )
+        .await?;
+        update_verified(
+            &mut regs,
+            Register::CTRL6_C,
+            reg::FTYPE_MASK,
+            config.ctrl6_c(),
+        )
+        .await?;
+        update_verified(
+            &mut regs,
+            Register::CTRL4_C,
+            reg::LPF1_SEL_G,
+            config.ctrl4_c(),
+        )
+        .await?;
+        update_verified(
+            &mut regs,
+            Register::CTRL7_G,
+            FilterConfig::CTRL7_G_MASK,
+            config.ctrl7_g(),
+        )
+        .await
+    }
+}
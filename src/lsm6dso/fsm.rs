@@ -0,0 +1,244 @@
+use super::embedded::update_emb_func_route;
+use super::reg::{self, EmbeddedRegister, FsmOdr, MemoryBank};
+use super::{wait_for_event, with_bank, Error, InterruptPin, Lsm6dso, Register};
+use crate::register::RegisterBus;
+use crate::transport::Transport;
+use embassy_time::{Duration, Timer};
+use embedded_hal_async::digital::Wait;
+
+/// Line of a UCF configuration, as exported by ST's tools for the finite
+/// state machine and other embedded functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum UcfLine {
+    /// Register write
+    Write { address: u8, data: u8 },
+    /// Delay in milliseconds, for the device to settle
+    Wait(u32),
+}
+
+impl UcfLine {
+    /// Parse a UCF text line such as `Ac 01 80` or `WAIT 5`, `None` for
+    /// comments and malformed lines. Usable at build time or on the host to
+    /// turn a `.ucf` file into a `[UcfLine]` table.
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split_whitespace();
+        let line = match fields.next()? {
+            "Ac" => Self::Write {
+                address: u8::from_str_radix(fields.next()?, 16).ok()?,
+                data: u8::from_str_radix(fields.next()?, 16).ok()?,
+            },
+            "WAIT" => Self::Wait(fields.next()?.parse().ok()?),
+            _ => return None,
+        };
+        match fields.next() {
+            None => Some(line),
+            Some(_) => None,
+        }
+    }
+}
+
+/// Write `data` to the advanced embedded functions pages from `address`,
+/// with the page in the high byte. The embedded functions bank must be
+/// selected.
+async fn write_page<T: Transport>(
+    regs: &mut RegisterBus<'_, T>,
+    address: u16,
+    data: &[u8],
+) -> Result<(), T::Error> {
+    let [page, address] = address.to_be_bytes();
+    regs.modify_reg(EmbeddedRegister::PAGE_RW, reg::PAGE_WRITE, reg::PAGE_WRITE)
+        .await?;
+    regs.write_reg(
+        EmbeddedRegister::PAGE_SEL,
+        page << reg::PAGE_SEL_SHIFT | reg::PAGE_SEL_DEFAULT,
+    )
+    .await?;
+    regs.write_reg(EmbeddedRegister::PAGE_ADDRESS, address)
+        .await?;
+    // The page address advances after each PAGE_VALUE write
+    for &value in data {
+        regs.write_reg(EmbeddedRegister::PAGE_VALUE, value).await?;
+    }
+    regs.write_reg(EmbeddedRegister::PAGE_SEL, reg::PAGE_SEL_DEFAULT)
+        .await?;
+    regs.modify_reg(EmbeddedRegister::PAGE_RW, reg::PAGE_WRITE, 0)
+        .await?;
+    Ok(())
+}
+
+impl<T: Transport> Lsm6dso<T> {
+    /// Replay a UCF configuration, such as an FSM program, honouring its
+    /// waits. The user bank is selected afterwards even if the sequence
+    /// leaves another one selected or a write fails. The bus stays locked
+    /// during the waits, since the sequence may have switched banks.
+    pub async fn load_ucf(&self, lines: &[UcfLine]) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        with_bank(&mut regs, MemoryBank::User, async |regs| {
+            for line in lines {
+                match *line {
+                    UcfLine::Write { address, data } => {
+                        regs.write_reg(address, data).await.map_err(Error::Bus)?
+                    }
+                    UcfLine::Wait(ms) => Timer::after(Duration::from_millis(ms.into())).await,
+                }
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    /// Enable the FSM slots set in `slots`, bit n for FSM n + 1. The FSM
+    /// engine runs while any slot is enabled.
+    pub async fn enable_fsm(&self, slots: u16) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        with_bank(&mut regs, MemoryBank::EmbeddedFunctions, async |regs| {
+            let [a, b] = slots.to_le_bytes();
+            regs.write_reg(EmbeddedRegister::FSM_ENABLE_A, a)
+                .await
+                .map_err(Error::Bus)?;
+            regs.write_reg(EmbeddedRegister::FSM_ENABLE_B, b)
+                .await
+                .map_err(Error::Bus)?;
+            regs.modify_reg(
+                EmbeddedRegister::EMB_FUNC_EN_B,
+                reg::FSM_EN,
+                if slots != 0 { reg::FSM_EN } else { 0 },
+            )
+            .await
+            .map_err(Error::Bus)
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Set the FSM output data rate
+    pub async fn set_fsm_odr(&self, odr: FsmOdr) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        with_bank(&mut regs, MemoryBank::EmbeddedFunctions, async |regs| {
+            regs.modify_reg(
+                EmbeddedRegister::EMB_FUNC_ODR_CFG_B,
+                reg::FSM_ODR_MASK,
+                odr as u8,
+            )
+            .await
+            .map_err(Error::Bus)
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Set the long counter value at which the FSM long counter event fires
+    pub async fn set_fsm_long_counter_timeout(&self, timeout: u16) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        with_bank(&mut regs, MemoryBank::EmbeddedFunctions, async |regs| {
+            write_page(regs, reg::FSM_LC_TIMEOUT_L, &timeout.to_le_bytes())
+                .await
+                .map_err(Error::Bus)
+        })
+        .await
+    }
+
+    /// Read the FSM long counter
+    pub async fn fsm_long_counter(&self) -> Result<u16, Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let mut buffer = [0u8; 2];
+        with_bank(&mut regs, MemoryBank::EmbeddedFunctions, async |regs| {
+            regs.read_regs(EmbeddedRegister::FSM_LONG_COUNTER_L, &mut buffer)
+                .await
+                .map_err(Error::Bus)
+        })
+        .await?;
+        Ok(u16::from_le_bytes(buffer))
+    }
+
+    /// Reset the FSM long counter to zero
+    pub async fn reset_fsm_long_counter(&self) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        with_bank(&mut regs, MemoryBank::EmbeddedFunctions, async |regs| {
+            regs.write_reg(EmbeddedRegister::FSM_LONG_COUNTER_CLEAR, reg::FSM_LC_CLEAR)
+                .await
+                .map_err(Error::Bus)
+        })
+        .await
+    }
+
+    /// Route the events of the FSM slots set in `slots` to `pin`, bit n for
+    /// FSM n + 1
+    pub async fn route_fsm(&self, pin: InterruptPin, slots: u16) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let (int_a, int_b) = match pin {
+            InterruptPin::Int1 => (EmbeddedRegister::FSM_INT1_A, EmbeddedRegister::FSM_INT1_B),
+            InterruptPin::Int2 => (EmbeddedRegister::FSM_INT2_A, EmbeddedRegister::FSM_INT2_B),
+        };
+        with_bank(&mut regs, MemoryBank::EmbeddedFunctions, async |regs| {
+            let [a, b] = slots.to_le_bytes();
+            regs.write_reg(int_a, a).await.map_err(Error::Bus)?;
+            regs.write_reg(int_b, b).await.map_err(Error::Bus)
+        })
+        .await?;
+        update_emb_func_route(&mut regs, pin).await
+    }
+
+    /// Read which FSM slots fired from the main page copy of FSM_STATUS,
+    /// bit n for FSM n + 1, clearing latched events
+    pub async fn fsm_status(&self) -> Result<u16, Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let mut status = [0u8; 2];
+        regs.read_regs(Register::FSM_STATUS_A_MAINPAGE, &mut status)
+            .await
+            .map_err(Error::Bus)?;
+        Ok(u16::from_le_bytes(status))
+    }
+
+    /// Read the FSM output registers, one per slot
+    pub async fn fsm_outputs(&self) -> Result<[u8; reg::FSM_SLOTS], Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let mut outputs = [0u8; reg::FSM_SLOTS];
+        with_bank(&mut regs, MemoryBank::EmbeddedFunctions, async |regs| {
+            regs.read_regs(EmbeddedRegister::FSM_OUTS1, &mut outputs)
+                .await
+                .map_err(Error::Bus)
+        })
+        .await?;
+        Ok(outputs)
+    }
+
+    /// Wait for FSM events signalled on `pin`, routed with
+    /// [`route_fsm`](Self::route_fsm), and return the slots that fired. Pin
+    /// assertions without an FSM event are skipped.
+    pub async fn wait_for_fsm(&self, pin: &mut impl Wait) -> Result<u16, Error<T::Error>> {
+        wait_for_event(pin, async || {
+            let status = self.fsm_status().await?;
+            Ok((status != 0).then_some(status))
+        })
+        .await
+    }
+}
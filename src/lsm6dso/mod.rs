@@ -1,53 +1,381 @@
-use core::fmt::Error;
-use defmt::*;
-use embassy_stm32::{dma::NoDma, i2c::I2c, peripherals};
-use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
-use embassy_sync::mutex::Mutex;
+use crate::measurement::{Acceleration, AngularRate, Temperature};
+use crate::register::RegisterBus;
+use crate::transport::{I2cTransport, SpiTransport, Transport};
+use crate::{SharedBus, SlaveAddr};
+use embedded_hal::digital::Error as _;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::I2c;
+use embedded_hal_async::spi::SpiDevice;
+mod activity;
+mod config;
+mod embedded;
+mod fifo;
+mod filter;
+mod fsm;
+mod offset;
+mod ois;
+mod orientation;
+mod power;
 mod reg;
-pub use reg::Register;
+mod shub;
+mod tap;
+mod timestamp;
+pub use activity::{FreeFallConfig, MotionEvent, MotionEvents, WakeUpConfig};
+pub use config::Lsm6dsoConfig;
+pub use embedded::{EmbeddedEvents, EmbeddedFunction};
+pub use fifo::{FifoConfig, FifoSample, FifoStatus};
+pub use filter::FilterConfig;
+pub use fsm::UcfLine;
+pub use offset::AccOffsetConfig;
+pub use ois::{Lsm6dsoOis, OisConfig, OisSample};
+pub use orientation::{Orientation, OrientationConfig};
+pub use reg::{
+    AccBandwidth, AccBdr, AccMode, AccOdr, AccScale, FifoMode, FreeFallThreshold, FsmOdr, GyrBdr,
+    GyrMode, GyrOdr, GyrScale, GyroHpfCutoff, GyroLpfLevel, InactivityMode, InterruptPin,
+    OisAccBandwidth, OisGyrLpf, Register, Rounding, ShubOdr, SixdThreshold, TapPriority,
+    TemperatureBdr, TimestampBatch,
+};
+pub use shub::{HubLis2mdl, SensorHubRead, SensorHubStatus};
+pub use tap::{Axis, TapConfig, TapEvent, TapEvents, TapKind};
+pub use timestamp::TimestampCorrelator;
 
-type Channel1 =
-    Mutex<CriticalSectionRawMutex, Option<I2c<'static, peripherals::I2C1, NoDma, NoDma>>>;
+/// LSM6DSO driver error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error<E> {
+    /// Bus error
+    Bus(E),
+    /// Data-ready pin error
+    Pin(embedded_hal::digital::ErrorKind),
+    /// The shared bus has not been initialized yet
+    BusNotInitialized,
+    /// WHO_AM_I returned an unexpected value
+    WrongDeviceId(u8),
+    /// A configuration register did not read back the value written to it
+    ConfigMismatch {
+        register: Register,
+        written: u8,
+        read: u8,
+    },
+    /// No new accelerometer or gyroscope data is available
+    DataNotReady,
+    /// The filter configuration is not valid, on its own or at the current
+    /// gyroscope ODR
+    InvalidFilterConfig,
+    /// The power mode is not available at the current ODR or with the other
+    /// sensor running
+    InvalidPowerMode,
+    /// A sensor hub slave did not acknowledge
+    SensorHubNack,
+    /// A sensor hub write did not complete, the accelerometer must be
+    /// running to clock the sensor hub
+    SensorHubTimeout,
+}
+
+/// Raw output register counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RawSample {
+    pub temperature: i16,
+    pub angular_rate: [i16; 3],
+    pub acceleration: [i16; 3],
+}
+
+impl RawSample {
+    /// Convert the counts to physical units using the given full scales
+    pub fn scale(self, acc_scale: AccScale, gyr_scale: GyrScale) -> Sample {
+        let [x, y, z] = self
+            .acceleration
+            .map(|v| f32::from(v) * acc_scale.mg_per_lsb() / 1000.0);
+        let acceleration = Acceleration { x, y, z };
+        let [x, y, z] = self
+            .angular_rate
+            .map(|v| f32::from(v) * gyr_scale.mdps_per_lsb() / 1000.0);
+        let angular_rate = AngularRate { x, y, z };
+        Sample {
+            temperature: Temperature(
+                f32::from(self.temperature) / reg::LSB_PER_DEG + reg::REF_TEMP,
+            ),
+            angular_rate,
+            acceleration,
+        }
+    }
+}
 
-pub struct Lsm6dso {
-    i2c: &'static Channel1,
+/// Gyroscope, accelerometer and temperature reading
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Sample {
+    pub temperature: Temperature,
+    pub angular_rate: AngularRate,
+    pub acceleration: Acceleration,
 }
 
-impl Lsm6dso {
+pub struct Lsm6dso<T: Transport> {
+    bus: &'static SharedBus<T::Bus>,
+    transport: T,
+    acc_scale: AccScale,
+    gyr_scale: GyrScale,
+}
+
+impl<I2C: I2c + 'static> Lsm6dso<I2cTransport<I2C>> {
     #[must_use]
-    pub fn new(i2c: &'static Channel1) -> Self {
-        Self { i2c }
+    pub fn new(i2c: &'static SharedBus<I2C>, address: SlaveAddr) -> Self {
+        Self::with_transport(
+            i2c,
+            I2cTransport::new(address.addr(reg::I2C_SAD, reg::I2C_SAD_ALT)),
+        )
     }
+}
 
-    pub async fn check_device_id(&self) -> Result<bool, Error> {
-        let mut data = [0u8, 1];
+impl<SPI: SpiDevice + 'static> Lsm6dso<SpiTransport<SPI>> {
+    /// 4-wire SPI, the power-up default
+    #[must_use]
+    pub fn new_spi(spi: &'static SharedBus<SPI>) -> Self {
+        Self::with_transport(spi, SpiTransport::new())
+    }
 
-        let mut i2c_unlocked = self.i2c.lock().await;
+    /// Disable the I2C interface, so that traffic to other devices on lines
+    /// shared with the SPI bus cannot be mistaken for I2C transfers
+    pub async fn disable_i2c(&self) -> Result<(), Error<SPI::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
 
-        let i2c_mut = i2c_unlocked.as_mut().ok_or(Error).unwrap();
+        update_verified(&mut regs, Register::CTRL4_C, reg::I2C_dis, reg::I2C_dis).await
+    }
+}
 
-        match i2c_mut.blocking_write_read(reg::I2C_SAD, &[Register::WHO_AM_I as u8], &mut data) {
-            Ok(()) => {
-                info!("Whoami: {}", data[0]);
-                if data[0] == reg::DEVICE_ID {
-                    Ok(true)
-                } else {
-                    Ok(false)
-                }
-            }
-            Err(e) => {
-                error!("I2C Error: {:?}", e);
-                Err(Error)
+impl<T: Transport> Lsm6dso<T> {
+    fn with_transport(bus: &'static SharedBus<T::Bus>, transport: T) -> Self {
+        Self {
+            bus,
+            transport,
+            acc_scale: AccScale::G2,
+            gyr_scale: GyrScale::Dps250,
+        }
+    }
+
+    pub async fn check_device_id(&self) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let id = regs
+            .read_reg(Register::WHO_AM_I)
+            .await
+            .map_err(Error::Bus)?;
+        info!("Whoami: {}", id);
+
+        if id == reg::DEVICE_ID {
+            Ok(())
+        } else {
+            Err(Error::WrongDeviceId(id))
+        }
+    }
+
+    /// Write the configuration fields and verify them by read-back, bits not
+    /// covered by the configuration are left untouched
+    pub async fn apply_config(&mut self, config: &Lsm6dsoConfig) -> Result<(), Error<T::Error>> {
+        {
+            let mut bus = self.bus.lock().await;
+            let mut regs =
+                RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+            update_verified(
+                &mut regs,
+                Register::CTRL3_C,
+                Lsm6dsoConfig::CTRL3_C_MASK,
+                config.ctrl3_c(),
+            )
+            .await?;
+            update_verified(
+                &mut regs,
+                Register::CTRL5_C,
+                reg::ROUNDING_MASK,
+                config.ctrl5_c(),
+            )
+            .await?;
+        }
+
+        self.init_accelerometer(config.acc_odr, config.acc_scale)
+            .await?;
+        self.init_gyroscope(config.gyr_odr, config.gyr_scale).await
+    }
+
+    /// Set the accelerometer output data rate and full scale
+    pub async fn init_accelerometer(
+        &mut self,
+        odr: AccOdr,
+        scale: AccScale,
+    ) -> Result<(), Error<T::Error>> {
+        {
+            let mut bus = self.bus.lock().await;
+            let mut regs =
+                RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+            // === CTRL1_XL (10h) ===
+            update_verified(
+                &mut regs,
+                Register::CTRL1_XL,
+                reg::ODR_XL_MASK | reg::FS_XL_MASK,
+                odr as u8 | scale as u8,
+            )
+            .await?;
+        }
+        self.acc_scale = scale;
+        Ok(())
+    }
+
+    /// Set the gyroscope output data rate and full scale. Fails with
+    /// [`Error::InvalidPowerMode`] without writing anything when the
+    /// gyroscope would run with the accelerometer in ultra-low-power mode.
+    pub async fn init_gyroscope(
+        &mut self,
+        odr: GyrOdr,
+        scale: GyrScale,
+    ) -> Result<(), Error<T::Error>> {
+        {
+            let mut bus = self.bus.lock().await;
+            let mut regs =
+                RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+            let ulp = regs.read_reg(Register::CTRL5_C).await.map_err(Error::Bus)? & reg::XL_ULP_EN;
+            if ulp != 0 && !matches!(odr, GyrOdr::PowerDown) {
+                return Err(Error::InvalidPowerMode);
             }
+
+            // === CTRL2_G (11h) ===
+            update_verified(
+                &mut regs,
+                Register::CTRL2_G,
+                reg::ODR_G_MASK | reg::FS_G_MASK,
+                odr as u8 | scale as u8,
+            )
+            .await?;
+        }
+        self.gyr_scale = scale;
+        Ok(())
+    }
+
+    /// Route the accelerometer and gyroscope data-ready signals to `pin`. The
+    /// signal is active high unless H_LACTIVE is set, and stays asserted until
+    /// the output registers are read.
+    pub async fn route_data_ready(
+        &self,
+        pin: InterruptPin,
+        enable: bool,
+    ) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let (register, mask) = match pin {
+            InterruptPin::Int1 => (Register::INT1_CTRL, reg::INT1_DRDY_XL | reg::INT1_DRDY_G),
+            InterruptPin::Int2 => (Register::INT2_CTRL, reg::INT2_DRDY_XL | reg::INT2_DRDY_G),
+        };
+        update_verified(&mut regs, register, mask, if enable { mask } else { 0 }).await
+    }
+
+    /// Wait until the data-ready signal routed by
+    /// [`route_data_ready`](Self::route_data_ready) is asserted on `pin`
+    pub async fn wait_for_data(&self, pin: &mut impl Wait) -> Result<(), Error<T::Error>> {
+        pin.wait_for_high().await.map_err(|e| Error::Pin(e.kind()))
+    }
+
+    /// Read temperature, gyroscope and accelerometer output registers in one burst
+    pub async fn sample_raw(&self) -> Result<RawSample, Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let status = regs
+            .read_reg(Register::STATUS_REG)
+            .await
+            .map_err(Error::Bus)?;
+        if status & (reg::XLDA | reg::GDA) == 0 {
+            return Err(Error::DataNotReady);
         }
+
+        // OUT_TEMP_L (20h) .. OUTZ_H_A (2Dh)
+        let mut buffer = [0u8; 14];
+        regs.read_regs(Register::OUT_TEMP_L, &mut buffer)
+            .await
+            .map_err(Error::Bus)?;
+
+        let word = |i: usize| i16::from_le_bytes([buffer[i], buffer[i + 1]]);
+        Ok(RawSample {
+            temperature: word(0),
+            angular_rate: [word(2), word(4), word(6)],
+            acceleration: [word(8), word(10), word(12)],
+        })
     }
 
-    pub async fn apply_config(&self) {
-        self.init_accelerometer().await;
-        self.init_gyroscope().await;
+    /// Read temperature, angular rate and acceleration scaled to the
+    /// configured full scales
+    pub async fn sample(&self) -> Result<Sample, Error<T::Error>> {
+        let raw = self.sample_raw().await?;
+        Ok(raw.scale(self.acc_scale, self.gyr_scale))
     }
+}
 
-    pub async fn init_accelerometer(&self) {}
-    pub async fn init_gyroscope(&self) {}
+/// Update the bits of `register` selected by `mask` and verify them by read-back
+async fn update_verified<T: Transport>(
+    regs: &mut RegisterBus<'_, T>,
+    register: Register,
+    mask: u8,
+    value: u8,
+) -> Result<(), Error<T::Error>> {
+    let written = regs
+        .modify_reg(register, mask, value)
+        .await
+        .map_err(Error::Bus)?;
+    let read = regs.read_reg(register).await.map_err(Error::Bus)?;
 
+    if read == written {
+        Ok(())
+    } else {
+        Err(Error::ConfigMismatch {
+            register,
+            written,
+            read,
+        })
+    }
+}
+
+/// Run `f` with the register map switched to `bank`. The user bank is
+/// restored afterwards, also when `f` fails, and the bus stays locked
+/// throughout so no other access sees the switched map.
+async fn with_bank<'a, T: Transport, R>(
+    regs: &mut RegisterBus<'a, T>,
+    bank: reg::MemoryBank,
+    f: impl AsyncFnOnce(&mut RegisterBus<'a, T>) -> Result<R, Error<T::Error>>,
+) -> Result<R, Error<T::Error>> {
+    regs.write_reg(Register::FUNC_CFG_ACCESS, bank as u8)
+        .await
+        .map_err(Error::Bus)?;
+    let result = f(regs).await;
+    let restored = regs
+        .write_reg(Register::FUNC_CFG_ACCESS, reg::MemoryBank::User as u8)
+        .await
+        .map_err(Error::Bus);
+    result.and_then(|value| restored.map(|()| value))
+}
+
+/// Wait for `pin` to be asserted until `read` returns an event. The wait is
+/// level-triggered so an event already pending is not missed; latched events
+/// must be cleared on read, or the same event is returned again while the
+/// line is still high.
+async fn wait_for_event<E, R>(
+    pin: &mut impl Wait,
+    mut read: impl AsyncFnMut() -> Result<Option<R>, Error<E>>,
+) -> Result<R, Error<E>> {
+    loop {
+        pin.wait_for_high()
+            .await
+            .map_err(|e| Error::Pin(e.kind()))?;
+        if let Some(event) = read().await? {
+            return Ok(event);
+        }
+    }
 }
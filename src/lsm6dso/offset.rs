@@ -0,0 +1,151 @@
+use super::reg;
+use super::{update_verified, Error, Lsm6dso, Register};
+use crate::register::RegisterBus;
+use crate::transport::Transport;
+
+/// LSM6DSO accelerometer user offset configuration
+///
+/// Applied to the output, the offsets are subtracted on the accelerometer
+/// low-pass path, so the output registers, the FIFO and the embedded
+/// functions all see corrected data. The gyroscope has no user offset
+/// registers. The default configuration applies zero offsets to the output
+/// data.
+#[derive(Copy, Clone)]
+pub struct AccOffsetConfig {
+    offset: [f32; 3],
+    output: bool,
+    wake_up: bool,
+}
+
+impl Default for AccOffsetConfig {
+    fn default() -> Self {
+        Self {
+            offset: [0.0; 3],
+            output: true,
+            wake_up: false,
+        }
+    }
+}
+
+impl AccOffsetConfig {
+    /// X, Y and Z offsets in mg, saturated at ±1984 mg. Offsets up to
+    /// ±124 mg use the 2^-10 g weight, larger ones the 2^-6 g weight.
+    #[must_use]
+    pub fn offset_mg(mut self, offset: [f32; 3]) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Apply the offsets to the output data, otherwise they only reach the
+    /// wake-up function when enabled there
+    #[must_use]
+    pub fn output(mut self, enable: bool) -> Self {
+        self.output = enable;
+        self
+    }
+
+    /// Feed the wake-up function with the offset-corrected low-pass data
+    /// instead of the high-pass data
+    #[must_use]
+    pub fn wake_up(mut self, enable: bool) -> Self {
+        self.wake_up = enable;
+        self
+    }
+
+    /// Whether the offsets need the coarse 2^-6 g weight
+    fn coarse(&self) -> bool {
+        self.offset
+            .iter()
+            .any(|mg| mg.abs() / reg::OFS_USR_FINE_MG > reg::OFS_USR_MAX + 0.5)
+    }
+
+    /// Offset weight in mg/LSB
+    pub fn weight_mg(&self) -> f32 {
+        if self.coarse() {
+            reg::OFS_USR_COARSE_MG
+        } else {
+            reg::OFS_USR_FINE_MG
+        }
+    }
+
+    /// X_OFS_USR (73h), Y_OFS_USR (74h) and Z_OFS_USR (75h) values
+    pub fn ofs_usr(&self) -> [u8; 3] {
+        let weight = self.weight_mg();
+        self.offset.map(|mg| {
+            let lsb = mg / weight;
+            let lsb = if lsb < 0.0 { lsb - 0.5 } else { lsb + 0.5 };
+            lsb.clamp(-reg::OFS_USR_MAX, reg::OFS_USR_MAX) as i8 as u8
+        })
+    }
+
+    /// Offsets in mg as applied by the sensor, after quantization
+    pub fn applied_mg(&self) -> [f32; 3] {
+        let weight = self.weight_mg();
+        self.ofs_usr().map(|lsb| f32::from(lsb as i8) * weight)
+    }
+
+    /// CTRL6_C (15h) USR_OFF_W bit
+    pub fn ctrl6_c(&self) -> u8 {
+        if self.coarse() {
+            reg::USR_OFF_W
+        } else {
+            0
+        }
+    }
+
+    /// CTRL7_G (16h) USR_OFF_ON_OUT bit
+    pub fn ctrl7_g(&self) -> u8 {
+        if self.output {
+            reg::USR_OFF_ON_OUT
+        } else {
+            0
+        }
+    }
+
+    /// WAKE_UP_THS (5Bh) USR_OFF_ON_WU bit
+    pub fn wake_up_ths(&self) -> u8 {
+        if self.wake_up {
+            reg::USR_OFF_ON_WU
+        } else {
+            0
+        }
+    }
+}
+
+impl<T: Transport> Lsm6dso<T> {
+    /// Write the accelerometer user offsets and verify them by read-back
+    pub async fn configure_acc_offset(
+        &self,
+        config: &AccOffsetConfig,
+    ) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        update_verified(
+            &mut regs,
+            Register::CTRL6_C,
+            reg::USR_OFF_W,
+            config.ctrl6_c(),
+        )
+        .await?;
+        let [x, y, z] = config.ofs_usr();
+        update_verified(&mut regs, Register::X_OFS_USR, 0xFF, x).await?;
+        update_verified(&mut regs, Register::Y_OFS_USR, 0xFF, y).await?;
+        update_verified(&mut regs, Register::Z_OFS_USR, 0xFF, z).await?;
+        update_verified(
+            &mut regs,
+            Register::WAKE_UP_THS,
+            reg::USR_OFF_ON_WU,
+            config.wake_up_ths(),
+        )
+        .await?;
+        update_verified(
+            &mut regs,
+            Register::CTRL7_G,
+            reg::USR_OFF_ON_OUT,
+            config.ctrl7_g(),
+        )
+        .await
+    }
+}
@@ -0,0 +1,250 @@
+use super::reg::{self, AccScale, GyrScale, GyroHpfCutoff, OisAccBandwidth, OisGyrLpf};
+use super::{update_verified, Error, Lsm6dso, Register};
+use crate::measurement::{Acceleration, AngularRate};
+use crate::register::RegisterBus;
+use crate::transport::{SpiTransport, Transport};
+use crate::SharedBus;
+use embedded_hal_async::spi::SpiDevice;
+
+/// LSM6DSO OIS chain configuration
+///
+/// The OIS chain runs at 6.66 kHz with its own filters, independent of the
+/// primary output data rate. The default configuration enables the gyroscope
+/// chain at 250 dps with the 297 Hz low-pass filter and no high-pass filter,
+/// and leaves the accelerometer chain off.
+#[derive(Copy, Clone)]
+pub struct OisConfig {
+    gyr_scale: GyrScale,
+    gyr_lpf: OisGyrLpf,
+    gyr_hpf: Option<GyroHpfCutoff>,
+    accelerometer: bool,
+    acc_scale: AccScale,
+    acc_bandwidth: OisAccBandwidth,
+}
+
+impl Default for OisConfig {
+    fn default() -> Self {
+        Self {
+            gyr_scale: GyrScale::Dps250,
+            gyr_lpf: OisGyrLpf::Hz297,
+            gyr_hpf: None,
+            accelerometer: false,
+            acc_scale: AccScale::G2,
+            acc_bandwidth: OisAccBandwidth::Hz289,
+        }
+    }
+}
+
+impl OisConfig {
+    /// CTRL1_OIS bits owned by the configuration
+    pub(crate) const CTRL1_OIS_MASK: u8 = reg::MODE4_EN | reg::FS_G_OIS_MASK | reg::OIS_EN_SPI2;
+    /// CTRL2_OIS bits owned by the configuration
+    pub(crate) const CTRL2_OIS_MASK: u8 = reg::FTYPE_OIS_MASK | reg::HPM_OIS_MASK | reg::HP_EN_OIS;
+    /// CTRL3_OIS bits owned by the configuration
+    pub(crate) const CTRL3_OIS_MASK: u8 = reg::FS_XL_OIS_MASK | reg::FILTER_XL_CONF_OIS_MASK;
+
+    /// Gyroscope full scale
+    #[must_use]
+    pub fn gyr_scale(mut self, scale: GyrScale) -> Self {
+        self.gyr_scale = scale;
+        self
+    }
+
+    /// Gyroscope low-pass filter cutoff
+    #[must_use]
+    pub fn gyr_lpf(mut self, cutoff: OisGyrLpf) -> Self {
+        self.gyr_lpf = cutoff;
+        self
+    }
+
+    /// Gyroscope high-pass filter cutoff, `None` to bypass the filter
+    #[must_use]
+    pub fn gyr_hpf(mut self, cutoff: Option<GyroHpfCutoff>) -> Self {
+        self.gyr_hpf = cutoff;
+        self
+    }
+
+    /// Accelerometer chain, run alongside the gyroscope chain
+    #[must_use]
+    pub fn accelerometer(mut self, enable: bool) -> Self {
+        self.accelerometer = enable;
+        self
+    }
+
+    /// Accelerometer full scale
+    #[must_use]
+    pub fn acc_scale(mut self, scale: AccScale) -> Self {
+        self.acc_scale = scale;
+        self
+    }
+
+    /// Accelerometer bandwidth
+    #[must_use]
+    pub fn acc_bandwidth(mut self, bandwidth: OisAccBandwidth) -> Self {
+        self.acc_bandwidth = bandwidth;
+        self
+    }
+
+    /// CTRL1_OIS (70h) value
+    pub fn ctrl1_ois(&self) -> u8 {
+        let mut reg = self.gyr_scale as u8 | reg::OIS_EN_SPI2;
+        if self.accelerometer {
+            reg |= reg::MODE4_EN;
+        }
+        reg
+    }
+
+    /// CTRL2_OIS (71h) value
+    pub fn ctrl2_ois(&self) -> u8 {
+        let mut reg = self.gyr_lpf as u8;
+        if let Some(cutoff) = self.gyr_hpf {
+            reg |= cutoff as u8 | reg::HP_EN_OIS;
+        }
+        reg
+    }
+
+    /// CTRL3_OIS (72h) value
+    pub fn ctrl3_ois(&self) -> u8 {
+        (self.acc_scale as u8) << reg::FS_XL_OIS_SHIFT | self.acc_bandwidth as u8
+    }
+}
+
+/// OIS chain gyroscope and accelerometer reading
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct OisSample {
+    pub angular_rate: AngularRate,
+    /// Zero unless the accelerometer chain is enabled
+    pub acceleration: Acceleration,
+}
+
+/// LSM6DSO auxiliary SPI interface, which configures the OIS chain and
+/// reads its output. The OIS output registers share their addresses with
+/// the primary ones but hold the OIS chain data.
+pub struct Lsm6dsoOis<SPI: SpiDevice + 'static> {
+    bus: &'static SharedBus<SPI>,
+    transport: SpiTransport<SPI>,
+    acc_scale: AccScale,
+    gyr_scale: GyrScale,
+}
+
+impl<SPI: SpiDevice + 'static> Lsm6dsoOis<SPI> {
+    /// 4-wire auxiliary SPI, the power-up default
+    #[must_use]
+    pub fn new(spi: &'static SharedBus<SPI>) -> Self {
+        Self {
+            bus: spi,
+            transport: SpiTransport::new(),
+            acc_scale: AccScale::G2,
+            gyr_scale: GyrScale::Dps250,
+        }
+    }
+
+    /// Write the OIS configuration, enabling the chain, and verify it by
+    /// read-back. Bits not covered by the configuration are left untouched.
+    pub async fn configure(&mut self, config: &OisConfig) -> Result<(), Error<SPI::Error>> {
+        {
+            let mut bus = self.bus.lock().await;
+            let mut regs =
+                RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+            update_verified(
+                &mut regs,
+                Register::CTRL2_OIS,
+                OisConfig::CTRL2_OIS_MASK,
+                config.ctrl2_ois(),
+            )
+            .await?;
+            update_verified(
+                &mut regs,
+                Register::CTRL3_OIS,
+                OisConfig::CTRL3_OIS_MASK,
+                config.ctrl3_ois(),
+            )
+            .await?;
+            update_verified(
+                &mut regs,
+                Register::CTRL1_OIS,
+                OisConfig::CTRL1_OIS_MASK,
+                config.ctrl1_ois(),
+            )
+            .await?;
+        }
+        self.acc_scale = config.acc_scale;
+        self.gyr_scale = config.gyr_scale;
+        Ok(())
+    }
+
+    /// Route the OIS data-ready signal to INT2, taking priority over every
+    /// other INT2 route
+    pub async fn route_data_ready(&self, enable: bool) -> Result<(), Error<SPI::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let mask = reg::INT2_DRDY_OIS;
+        update_verified(
+            &mut regs,
+            Register::INT_OIS,
+            mask,
+            if enable { mask } else { 0 },
+        )
+        .await
+    }
+
+    /// Read the OIS gyroscope and accelerometer output registers in one
+    /// burst, scaled to the configured full scales
+    pub async fn sample(&self) -> Result<OisSample, Error<SPI::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        // STATUS_SPIAux
+        let status = regs
+            .read_reg(Register::STATUS_REG)
+            .await
+            .map_err(Error::Bus)?;
+        if status & reg::GDA == 0 || status & reg::GYRO_SETTLING != 0 {
+            return Err(Error::DataNotReady);
+        }
+
+        // OUTX_L_G (22h) .. OUTZ_H_A (2Dh)
+        let mut buffer = [0u8; 12];
+        regs.read_regs(Register::OUTX_L_G, &mut buffer)
+            .await
+            .map_err(Error::Bus)?;
+
+        let axis = |i: usize| f32::from(i16::from_le_bytes([buffer[2 * i], buffer[2 * i + 1]]));
+        let [x, y, z] = [0, 1, 2].map(|i| axis(i) * self.gyr_scale.mdps_per_lsb() / 1000.0);
+        let angular_rate = AngularRate { x, y, z };
+        let [x, y, z] = [3, 4, 5].map(|i| axis(i) * self.acc_scale.mg_per_lsb() / 1000.0);
+        let acceleration = Acceleration { x, y, z };
+        Ok(OisSample {
+            angular_rate,
+            acceleration,
+        })
+    }
+}
+
+impl<T: Transport> Lsm6dso<T> {
+    /// Hand control of the OIS chain to the primary interface and switch it
+    /// on or off. The chain must have been configured and enabled once
+    /// through the auxiliary SPI with [`Lsm6dsoOis::configure`].
+    pub async fn enable_ois(&self, enable: bool) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        update_verified(
+            &mut regs,
+            Register::CTRL7_G,
+            reg::OIS_ON_EN | reg::OIS_ON,
+            if enable {
+                reg::OIS_ON_EN | reg::OIS_ON
+            } else {
+                reg::OIS_ON_EN
+            },
+        )
+        .await
+    }
+}
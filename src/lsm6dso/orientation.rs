@@ -0,0 +1,187 @@
+use super::reg::{self, SixdThreshold};
+use super::{update_verified, wait_for_event, Error, InterruptPin, Lsm6dso, Register};
+use crate::register::RegisterBus;
+use crate::transport::Transport;
+use embedded_hal_async::digital::Wait;
+
+/// LSM6DSO 6D/4D orientation configuration
+///
+/// An axis is reported as pointing up or down once it is within the
+/// threshold angle of the vertical. The default configuration uses 60
+/// degrees in 6D mode on the unfiltered accelerometer data.
+#[derive(Copy, Clone)]
+pub struct OrientationConfig {
+    threshold: SixdThreshold,
+    four_d: bool,
+    low_pass: bool,
+}
+
+impl Default for OrientationConfig {
+    fn default() -> Self {
+        Self {
+            threshold: SixdThreshold::Deg60,
+            four_d: false,
+            low_pass: false,
+        }
+    }
+}
+
+impl OrientationConfig {
+    /// TAP_THS_6D bits owned by the configuration
+    pub(crate) const TAP_THS_6D_MASK: u8 = reg::D4D_EN | reg::SIXD_THS_MASK;
+
+    /// Threshold angle
+    #[must_use]
+    pub fn threshold(mut self, threshold: SixdThreshold) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// 4D mode, the Z axis is not reported
+    #[must_use]
+    pub fn four_d(mut self, enable: bool) -> Self {
+        self.four_d = enable;
+        self
+    }
+
+    /// Detect on the LPF2 filtered accelerometer data
+    #[must_use]
+    pub fn low_pass(mut self, enable: bool) -> Self {
+        self.low_pass = enable;
+        self
+    }
+
+    /// TAP_THS_6D (59h) D4D_EN and SIXD_THS bits
+    pub fn tap_ths_6d(&self) -> u8 {
+        let mut reg = self.threshold as u8;
+        if self.four_d {
+            reg |= reg::D4D_EN;
+        }
+        reg
+    }
+
+    /// CTRL8_XL (17h) LOW_PASS_ON_6D bit
+    pub fn ctrl8_xl(&self) -> u8 {
+        if self.low_pass {
+            reg::LOW_PASS_ON_6D
+        } else {
+            0
+        }
+    }
+}
+
+/// Axis pointing up or down
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Orientation {
+    XUp,
+    XDown,
+    YUp,
+    YDown,
+    ZUp,
+    ZDown,
+}
+
+impl Orientation {
+    /// Decode the position bits of a D6D_SRC value, `None` while no axis is
+    /// within the threshold angle
+    pub fn from_src(src: u8) -> Option<Self> {
+        [
+            (reg::XH, Orientation::XUp),
+            (reg::XL, Orientation::XDown),
+            (reg::YH, Orientation::YUp),
+            (reg::YL, Orientation::YDown),
+            (reg::ZH, Orientation::ZUp),
+            (reg::ZL, Orientation::ZDown),
+        ]
+        .into_iter()
+        .find(|(mask, _)| src & mask != 0)
+        .map(|(_, orientation)| orientation)
+    }
+}
+
+impl<T: Transport> Lsm6dso<T> {
+    /// Write the orientation configuration and verify it by read-back
+    pub async fn configure_orientation(
+        &self,
+        config: &OrientationConfig,
+    ) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        update_verified(
+            &mut regs,
+            Register::TAP_THS_6D,
+            OrientationConfig::TAP_THS_6D_MASK,
+            config.tap_ths_6d(),
+        )
+        .await?;
+        update_verified(
+            &mut regs,
+            Register::CTRL8_XL,
+            reg::LOW_PASS_ON_6D,
+            config.ctrl8_xl(),
+        )
+        .await?;
+        update_verified(
+            &mut regs,
+            Register::TAP_CFG2,
+            reg::INTERRUPTS_ENABLE,
+            reg::INTERRUPTS_ENABLE,
+        )
+        .await
+    }
+
+    /// Route the orientation change event to `pin`
+    pub async fn route_orientation(
+        &self,
+        pin: InterruptPin,
+        enable: bool,
+    ) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let (register, mask) = match pin {
+            InterruptPin::Int1 => (Register::MD1_CFG, reg::INT1_6D),
+            InterruptPin::Int2 => (Register::MD2_CFG, reg::INT2_6D),
+        };
+        update_verified(&mut regs, register, mask, if enable { mask } else { 0 }).await
+    }
+
+    /// Read the current orientation from D6D_SRC, clearing a latched
+    /// orientation change
+    pub async fn orientation(&self) -> Result<Option<Orientation>, Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let src = regs.read_reg(Register::D6D_SRC).await.map_err(Error::Bus)?;
+        Ok(Orientation::from_src(src))
+    }
+
+    /// Read D6D_SRC and return the new orientation if it changed, clearing a
+    /// latched orientation change
+    pub async fn read_orientation_event(&self) -> Result<Option<Orientation>, Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let src = regs.read_reg(Register::D6D_SRC).await.map_err(Error::Bus)?;
+        if src & reg::D6D_SRC_IA == 0 {
+            return Ok(None);
+        }
+        Ok(Orientation::from_src(src))
+    }
+
+    /// Wait for an orientation change signalled on `pin`, routed with
+    /// [`route_orientation`](Self::route_orientation), and return the new
+    /// orientation. Pin assertions without a change in D6D_SRC are skipped.
+    pub async fn wait_for_orientation(
+        &self,
+        pin: &mut impl Wait,
+    ) -> Result<Orientation, Error<T::Error>> {
+        wait_for_event(pin, async || self.read_orientation_event().await).await
+    }
+}
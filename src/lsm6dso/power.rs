@@ -0,0 +1,105 @@
+use super::reg::{self, AccMode, GyrMode};
+use super::{update_verified, Error, Lsm6dso, Register};
+use crate::register::RegisterBus;
+use crate::transport::Transport;
+
+/// Whether the accelerometer can run in `mode` at the ODR_XL bits `odr`.
+/// A powered-down accelerometer accepts any mode.
+fn acc_mode_allows(mode: AccMode, odr: u8) -> bool {
+    match (mode, odr >> 4) {
+        (_, 0) | (AccMode::PowerDown | AccMode::HighPerf, _) => true,
+        // 1.6 Hz, 12.5 Hz .. 52 Hz
+        (AccMode::LowPower, n) => matches!(n, 0b1011 | 1..=3),
+        // 104 Hz, 208 Hz
+        (AccMode::Normal, n) => matches!(n, 4..=5),
+        // 1.6 Hz, 12.5 Hz .. 208 Hz
+        (AccMode::UltraLowPower, n) => matches!(n, 0b1011 | 1..=5),
+    }
+}
+
+/// Whether the gyroscope can run in `mode` at the ODR_G bits `odr`. A
+/// powered-down gyroscope accepts any mode.
+fn gyr_mode_allows(mode: GyrMode, odr: u8) -> bool {
+    match (mode, odr >> 4) {
+        (_, 0) | (GyrMode::PowerDown | GyrMode::Sleep | GyrMode::HighPerf, _) => true,
+        // 12.5 Hz .. 52 Hz
+        (GyrMode::LowPower, n) => matches!(n, 1..=3),
+        // 104 Hz, 208 Hz
+        (GyrMode::Normal, n) => matches!(n, 4..=5),
+    }
+}
+
+impl<T: Transport> Lsm6dso<T> {
+    /// Switch the accelerometer power mode at its current ODR, which must
+    /// be within the range of the mode. Ultra-low-power needs the gyroscope
+    /// powered down; entering or leaving it briefly powers the accelerometer
+    /// down. Fails with [`Error::InvalidPowerMode`] without writing anything
+    /// when the mode is not available.
+    pub async fn set_acc_mode(&self, mode: AccMode) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let odr = regs
+            .read_reg(Register::CTRL1_XL)
+            .await
+            .map_err(Error::Bus)?
+            & reg::ODR_XL_MASK;
+        let gyr_odr = regs.read_reg(Register::CTRL2_G).await.map_err(Error::Bus)? & reg::ODR_G_MASK;
+        let ulp = regs.read_reg(Register::CTRL5_C).await.map_err(Error::Bus)? & reg::XL_ULP_EN;
+        if !acc_mode_allows(mode, odr) || (mode == AccMode::UltraLowPower && gyr_odr != 0) {
+            return Err(Error::InvalidPowerMode);
+        }
+
+        let (xl_ulp_en, xl_hm_mode) = match mode {
+            AccMode::UltraLowPower => (reg::XL_ULP_EN, 0),
+            AccMode::LowPower | AccMode::Normal => (0, reg::XL_HM_MODE),
+            AccMode::PowerDown | AccMode::HighPerf => (0, 0),
+        };
+        // Ultra-low-power is entered and left from power-down
+        if ulp != xl_ulp_en {
+            update_verified(&mut regs, Register::CTRL1_XL, reg::ODR_XL_MASK, 0).await?;
+        }
+        update_verified(&mut regs, Register::CTRL5_C, reg::XL_ULP_EN, xl_ulp_en).await?;
+        update_verified(&mut regs, Register::CTRL6_C, reg::XL_HM_MODE, xl_hm_mode).await?;
+        let odr = if mode == AccMode::PowerDown { 0 } else { odr };
+        update_verified(&mut regs, Register::CTRL1_XL, reg::ODR_XL_MASK, odr).await
+    }
+
+    /// Switch the gyroscope power mode at its current ODR, which must be
+    /// within the range of the mode. Sleep keeps the high-performance or
+    /// low-power setting for the wake-up. Fails with
+    /// [`Error::InvalidPowerMode`] without writing anything when the mode is
+    /// not available, or with the accelerometer in ultra-low-power unless
+    /// powering down.
+    pub async fn set_gyro_mode(&self, mode: GyrMode) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let odr = regs.read_reg(Register::CTRL2_G).await.map_err(Error::Bus)? & reg::ODR_G_MASK;
+        let ulp = regs.read_reg(Register::CTRL5_C).await.map_err(Error::Bus)? & reg::XL_ULP_EN;
+        if !gyr_mode_allows(mode, odr) || (mode != GyrMode::PowerDown && ulp != 0) {
+            return Err(Error::InvalidPowerMode);
+        }
+
+        let sleep = if mode == GyrMode::Sleep {
+            reg::SLEEP_G
+        } else {
+            0
+        };
+        update_verified(&mut regs, Register::CTRL4_C, reg::SLEEP_G, sleep).await?;
+        match mode {
+            GyrMode::Sleep => Ok(()),
+            GyrMode::LowPower | GyrMode::Normal => {
+                update_verified(&mut regs, Register::CTRL7_G, reg::G_HM_MODE, reg::G_HM_MODE).await
+            }
+            GyrMode::HighPerf => {
+                update_verified(&mut regs, Register::CTRL7_G, reg::G_HM_MODE, 0).await
+            }
+            GyrMode::PowerDown => {
+                update_verified(&mut regs, Register::CTRL2_G, reg::ODR_G_MASK, 0).await
+            }
+        }
+    }
+}
@@ -1,25 +1,44 @@
 #![allow(non_upper_case_globals)]
 #![allow(dead_code)]
+use crate::register::RegisterAddress;
 
 /// I2C slave address
 pub const I2C_SAD: u8 = 0b1101011;
+/// I2C slave address with SA0 low
+pub const I2C_SAD_ALT: u8 = 0b1101010;
 
-/// Operating mode
-#[allow(dead_code)]
+pub const LSB_PER_DEG: f32 = 256.0;
+pub const REF_TEMP: f32 = 25.0;
+
+/// Accelerometer power mode
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum AccMode {
+    /// Powered down, ODR_XL cleared
     PowerDown,
+    /// 1.6 Hz to 208 Hz, only with the gyroscope powered down
     UltraLowPower,
+    /// 1.6 Hz to 52 Hz
     LowPower,
+    /// 104 Hz and 208 Hz
     Normal,
+    /// Any ODR, the power-up default
     HighPerf,
 }
 
-/// Operating mode
-#[allow(dead_code)]
+/// Gyroscope power mode
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum GyrMode {
+    /// Powered down, ODR_G cleared
     PowerDown,
+    /// Sleep with a fast wake-up, the ODR is kept
+    Sleep,
+    /// 12.5 Hz to 52 Hz
     LowPower,
+    /// 104 Hz and 208 Hz
     Normal,
+    /// Any ODR, the power-up default
     HighPerf,
 }
 
@@ -27,7 +46,8 @@ pub enum GyrMode {
 #[allow(dead_code)]
 #[allow(non_camel_case_types)]
 #[allow(clippy::upper_case_acronyms)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Register {
     FUNC_CFG_ACCESS = 0x01,
     PIN_CTRL = 0x02,
@@ -107,10 +127,271 @@ pub enum Register {
     FIFO_DATA_OUT_Z_H = 0x7E,
 }
 
+#[allow(dead_code)]
+impl Register {
+    /// Get register address
+    pub fn addr(self) -> u8 {
+        self as u8
+    }
+}
+
+impl RegisterAddress for Register {
+    fn addr(self) -> u8 {
+        Register::addr(self)
+    }
+}
+
+/// Embedded functions register mapping, accessed through
+/// [`MemoryBank::EmbeddedFunctions`]
+#[allow(dead_code)]
+#[allow(non_camel_case_types)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum EmbeddedRegister {
+    PAGE_SEL = 0x02,
+    EMB_FUNC_EN_A = 0x04,
+    EMB_FUNC_EN_B = 0x05,
+    PAGE_ADDRESS = 0x08,
+    PAGE_VALUE = 0x09,
+    EMB_FUNC_INT1 = 0x0A,
+    FSM_INT1_A = 0x0B,
+    FSM_INT1_B = 0x0C,
+    EMB_FUNC_INT2 = 0x0E,
+    FSM_INT2_A = 0x0F,
+    FSM_INT2_B = 0x10,
+    EMB_FUNC_STATUS = 0x12,
+    FSM_STATUS_A = 0x13,
+    FSM_STATUS_B = 0x14,
+    PAGE_RW = 0x17,
+    EMB_FUNC_FIFO_CFG = 0x44,
+    FSM_ENABLE_A = 0x46,
+    FSM_ENABLE_B = 0x47,
+    FSM_LONG_COUNTER_L = 0x48,
+    FSM_LONG_COUNTER_H = 0x49,
+    FSM_LONG_COUNTER_CLEAR = 0x4A,
+    FSM_OUTS1 = 0x4C,
+    EMB_FUNC_ODR_CFG_B = 0x5F,
+    STEP_COUNTER_L = 0x62,
+    STEP_COUNTER_H = 0x63,
+    EMB_FUNC_SRC = 0x64,
+    EMB_FUNC_INIT_A = 0x66,
+    EMB_FUNC_INIT_B = 0x67,
+}
+
+impl RegisterAddress for EmbeddedRegister {
+    fn addr(self) -> u8 {
+        self as u8
+    }
+}
+
+/// Sensor hub register mapping, accessed through [`MemoryBank::SensorHub`]
+#[allow(dead_code)]
+#[allow(non_camel_case_types)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SensorHubRegister {
+    SENSOR_HUB_1 = 0x02,
+    MASTER_CONFIG = 0x14,
+    SLV0_ADD = 0x15,
+    SLV0_SUBADD = 0x16,
+    SLV0_CONFIG = 0x17,
+    SLV1_ADD = 0x18,
+    SLV1_SUBADD = 0x19,
+    SLV1_CONFIG = 0x1A,
+    SLV2_ADD = 0x1B,
+    SLV2_SUBADD = 0x1C,
+    SLV2_CONFIG = 0x1D,
+    SLV3_ADD = 0x1E,
+    SLV3_SUBADD = 0x1F,
+    SLV3_CONFIG = 0x20,
+    DATAWRITE_SLV0 = 0x21,
+    STATUS_MASTER = 0x22,
+}
+
+impl RegisterAddress for SensorHubRegister {
+    fn addr(self) -> u8 {
+        self as u8
+    }
+}
+
+// === FUNC_CFG_ACCESS (01h) ===
+pub const FUNC_CFG_EN: u8 = 0b1000_0000;
+pub const SHUB_REG_ACCESS: u8 = 0b0100_0000;
+
+/// Register bank selected with FUNC_CFG_ACCESS, which stays mapped at 01h
+/// in every bank
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MemoryBank {
+    /// Main register map
+    User = 0b0000_0000,
+    /// Sensor hub registers
+    SensorHub = 0b0100_0000,
+    /// Embedded functions registers
+    EmbeddedFunctions = 0b1000_0000,
+}
+
+// === FIFO_CTRL1 (07h) ===
+/// Maximum FIFO watermark, WTM[8:0] spans FIFO_CTRL1 and FIFO_CTRL2
+pub const WTM_MAX: u16 = 0x1FF;
+
+// === FIFO_CTRL2 (08h) ===
+pub const STOP_ON_WTM: u8 = 0b1000_0000;
+pub const FIFO_COMPR_RT_EN: u8 = 0b0100_0000;
+pub const ODRCHG_EN: u8 = 0b0001_0000;
+pub const UNCOPTR_RATE_MASK: u8 = 0b0000_0110;
+pub const WTM8: u8 = 0b0000_0001;
+
+// === FIFO_CTRL3 (09h) ===
+pub const BDR_GY_MASK: u8 = 0b1111_0000;
+pub const BDR_XL_MASK: u8 = 0b0000_1111;
+
+/// Accelerometer FIFO batch data rate
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AccBdr {
+    /// Not batched in FIFO
+    NotBatched = 0b0000_0000,
+    /// 1.6 Hz
+    Hz1_6 = 0b0000_1011,
+    /// 12.5 Hz
+    Hz12_5 = 0b0000_0001,
+    /// 26 Hz
+    Hz26 = 0b0000_0010,
+    /// 52 Hz
+    Hz52 = 0b0000_0011,
+    /// 104 Hz
+    Hz104 = 0b0000_0100,
+    /// 208 Hz
+    Hz208 = 0b0000_0101,
+    /// 417 Hz
+    Hz417 = 0b0000_0110,
+    /// 833 Hz
+    Hz833 = 0b0000_0111,
+    /// 1.667 kHz
+    KHz1_667 = 0b0000_1000,
+    /// 3.333 kHz
+    KHz3_333 = 0b0000_1001,
+    /// 6.667 kHz
+    KHz6_667 = 0b0000_1010,
+}
+
+/// Gyroscope FIFO batch data rate
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum GyrBdr {
+    /// Not batched in FIFO
+    NotBatched = 0b0000_0000,
+    /// 6.5 Hz
+    Hz6_5 = 0b1011_0000,
+    /// 12.5 Hz
+    Hz12_5 = 0b0001_0000,
+    /// 26 Hz
+    Hz26 = 0b0010_0000,
+    /// 52 Hz
+    Hz52 = 0b0011_0000,
+    /// 104 Hz
+    Hz104 = 0b0100_0000,
+    /// 208 Hz
+    Hz208 = 0b0101_0000,
+    /// 417 Hz
+    Hz417 = 0b0110_0000,
+    /// 833 Hz
+    Hz833 = 0b0111_0000,
+    /// 1.667 kHz
+    KHz1_667 = 0b1000_0000,
+    /// 3.333 kHz
+    KHz3_333 = 0b1001_0000,
+    /// 6.667 kHz
+    KHz6_667 = 0b1010_0000,
+}
+
+// === FIFO_CTRL4 (0Ah) ===
+pub const DEC_TS_BATCH_MASK: u8 = 0b1100_0000;
+pub const ODR_T_BATCH_MASK: u8 = 0b0011_0000;
+pub const FIFO_MODE_MASK: u8 = 0b0000_0111;
+
+/// Timestamp batching decimation
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TimestampBatch {
+    /// Not batched in FIFO
+    NotBatched = 0b0000_0000,
+    /// Every batch data rate period
+    Every1 = 0b0100_0000,
+    /// Every 8 batch data rate periods
+    Every8 = 0b1000_0000,
+    /// Every 32 batch data rate periods
+    Every32 = 0b1100_0000,
+}
+
+/// Temperature FIFO batch data rate
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TemperatureBdr {
+    /// Not batched in FIFO
+    NotBatched = 0b0000_0000,
+    /// 1.6 Hz
+    Hz1_6 = 0b0001_0000,
+    /// 12.5 Hz
+    Hz12_5 = 0b0010_0000,
+    /// 52 Hz
+    Hz52 = 0b0011_0000,
+}
+
+/// FIFO mode selection
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FifoMode {
+    /// FIFO disabled and emptied
+    Bypass = 0b000,
+    /// Stop collecting data when the FIFO is full
+    Fifo = 0b001,
+    /// Continuous until trigger, then FIFO
+    ContinuousToFifo = 0b011,
+    /// Bypass until trigger, then continuous
+    BypassToContinuous = 0b100,
+    /// Overwrite the oldest samples when the FIFO is full
+    Continuous = 0b110,
+    /// Bypass until trigger, then FIFO
+    BypassToFifo = 0b111,
+}
+
+// === INT1_CTRL (0Dh) ===
+pub const DEN_DRDY_flag: u8 = 0b1000_0000;
+pub const INT1_CNT_BDR: u8 = 0b0100_0000;
+pub const INT1_FIFO_FULL: u8 = 0b0010_0000;
+pub const INT1_FIFO_OVR: u8 = 0b0001_0000;
+pub const INT1_FIFO_TH: u8 = 0b0000_1000;
+pub const INT1_BOOT: u8 = 0b0000_0100;
+pub const INT1_DRDY_G: u8 = 0b0000_0010;
+pub const INT1_DRDY_XL: u8 = 0b0000_0001;
+
+// === INT2_CTRL (0Eh) ===
+pub const INT2_CNT_BDR: u8 = 0b0100_0000;
+pub const INT2_FIFO_FULL: u8 = 0b0010_0000;
+pub const INT2_FIFO_OVR: u8 = 0b0001_0000;
+pub const INT2_FIFO_TH: u8 = 0b0000_1000;
+pub const INT2_DRDY_TEMP: u8 = 0b0000_0100;
+pub const INT2_DRDY_G: u8 = 0b0000_0010;
+pub const INT2_DRDY_XL: u8 = 0b0000_0001;
+
+/// Interrupt pin
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum InterruptPin {
+    Int1,
+    Int2,
+}
+
 // === WHO_AM_I (0Fh) ===
 pub const DEVICE_ID: u8 = 0b01101100;
 // === CTRL1_XL (10h) ===
 // Acclerometer control register 1
+pub const ODR_XL_MASK: u8 = 0b1111_0000;
+pub const FS_XL_MASK: u8 = 0b0000_1100;
 pub const LPF2_XL_EN: u8 = 0b0000_0010;
 
 /// Accelerometer Output Data Rate
@@ -143,6 +424,31 @@ pub enum AccOdr {
     KHz6_66 = 0b1010_0000,
 }
 
+impl AccOdr {
+    /// Output data rate in Hz, 0 when powered down
+    pub fn hz(self) -> f32 {
+        odr_hz(self as u8)
+    }
+}
+
+/// Output data rate in Hz of the ODR_XL or ODR_G bits
+fn odr_hz(bits: u8) -> f32 {
+    match bits >> 4 {
+        0b0001 => 12.5,
+        0b0010 => 26.0,
+        0b0011 => 52.0,
+        0b0100 => 104.0,
+        0b0101 => 208.0,
+        0b0110 => 416.0,
+        0b0111 => 833.0,
+        0b1000 => 1660.0,
+        0b1001 => 3330.0,
+        0b1010 => 6660.0,
+        0b1011 => 1.6,
+        _ => 0.0,
+    }
+}
+
 /// Accelerometer full-scale selection
 /// If XL_FS_MODE = '1' in CTRL8_XL max scale 8g, g16 becomes 2g see reference manual
 #[derive(Copy, Clone)]
@@ -158,8 +464,32 @@ pub enum AccScale {
     G8 = 0b0000_1100,
 }
 
+impl AccScale {
+    /// Sensitivity in mg/LSB
+    pub fn mg_per_lsb(self) -> f32 {
+        match self {
+            AccScale::G2 => 0.061,
+            AccScale::G4 => 0.122,
+            AccScale::G8 => 0.244,
+            AccScale::G16 => 0.488,
+        }
+    }
+
+    /// Full scale in g
+    pub fn full_scale_g(self) -> f32 {
+        match self {
+            AccScale::G2 => 2.0,
+            AccScale::G4 => 4.0,
+            AccScale::G8 => 8.0,
+            AccScale::G16 => 16.0,
+        }
+    }
+}
+
 // === CTRL2_G (11h) ===
 // Acclerometer control register 1
+pub const ODR_G_MASK: u8 = 0b1111_0000;
+pub const FS_G_MASK: u8 = 0b0000_1110;
 pub const FS_125: u8 = 0b0000_0010;
 
 /// Gyroscope Output Data Rate
@@ -192,10 +522,19 @@ pub enum GyrOdr {
     KHz6_66 = 0b1010_0000,
 }
 
+impl GyrOdr {
+    /// Output data rate in Hz, 0 when powered down
+    pub fn hz(self) -> f32 {
+        odr_hz(self as u8)
+    }
+}
+
 /// Gyroscope full-scale selection
 #[derive(Copy, Clone)]
 #[cfg_attr(feature = "out_f32", derive(FromPrimitive))]
 pub enum GyrScale {
+    /// 125 dps, sets FS_125
+    Dps125 = 0b0000_0010,
     /// 250 dps
     Dps250 = 0b0000_0000,
     /// 500 dps
@@ -206,6 +545,19 @@ pub enum GyrScale {
     Dps2000 = 0b0000_1100,
 }
 
+impl GyrScale {
+    /// Sensitivity in mdps/LSB
+    pub fn mdps_per_lsb(self) -> f32 {
+        match self {
+            GyrScale::Dps125 => 4.375,
+            GyrScale::Dps250 => 8.75,
+            GyrScale::Dps500 => 17.5,
+            GyrScale::Dps1000 => 35.0,
+            GyrScale::Dps2000 => 70.0,
+        }
+    }
+}
+
 // === CTRL3_C (12h) ===
 pub const BOOT: u8 = 0b1000_0000;
 pub const BDU: u8 = 0b0100_0000;
@@ -224,6 +576,7 @@ pub const LPF1_SEL_G: u8 = 0b0000_0010;
 
 // === CTRL5_C (14h) ===
 pub const XL_ULP_EN: u8 = 0b1000_0000;
+pub const ROUNDING_MASK: u8 = 0b0110_0000;
 
 /// Circular burst-mode (rounding) read from the output registers. Default value: 00
 #[derive(Copy, Clone)]
@@ -266,6 +619,7 @@ pub enum GyroSeflTest {
 /// === CTRL6_C (15h) ===
 pub const XL_HM_MODE: u8 = 0b0001_0000;
 pub const USR_OFF_W: u8 = 0b0000_1000;
+pub const FTYPE_MASK: u8 = 0b0000_0111;
 
 /// Trigger mode selection
 #[derive(Copy, Clone)]
@@ -310,23 +664,38 @@ pub const HP_EN_G: u8 = 0b0100_0000;
 pub const OIS_ON_EN: u8 = 0b0000_0100;
 pub const USR_OFF_ON_OUT: u8 = 0b0000_0010;
 pub const OIS_ON: u8 = 0b0000_0001;
+pub const HPM_G_MASK: u8 = 0b0011_0000;
 
 /// Gyroscope High Pass filter
 #[derive(Copy, Clone)]
 #[cfg_attr(feature = "out_f32", derive(FromPrimitive))]
 pub enum GyroHpfCutoff {
-    /// Low pass1 4.2  8.3 16.6 33.0 67.0 136.6 239.2 304.2 328.5 335.5
+    /// 16 mHz
     MilliHz16 = 0b0000_0000,
-    /// Level two 4.2 8.3 16.6 33.0 67.0 130.5 192.4 220.7 229.6 232.0
+    /// 65 mHz
     MilliHz65 = 0b0001_0000,
-    /// Level     4.2 8.3 16.6 33.0 67.0 120.3 154.2 166.6 170.1 171.1
+    /// 260 mHz
     MilliHz260 = 0b0010_0000,
-    /// Level     4.2 8.3 16.6 33.0 67.0 137.1 281.8 453.2 559.2 609.0
+    /// 1.04 Hz
     Hz1 = 0b0011_0000,
 }
 
+impl GyroHpfCutoff {
+    /// Cutoff frequency in Hz
+    pub fn hz(self) -> f32 {
+        match self {
+            GyroHpfCutoff::MilliHz16 => 0.016,
+            GyroHpfCutoff::MilliHz65 => 0.065,
+            GyroHpfCutoff::MilliHz260 => 0.26,
+            GyroHpfCutoff::Hz1 => 1.04,
+        }
+    }
+}
+
 /// === CTRL8_XL (17h) ===
+pub const HPCF_XL_MASK: u8 = 0b1110_0000;
 pub const HP_REF_MODE_XL: u8 = 0b0001_0000;
+pub const HP_SLOPE_XL_EN: u8 = 0b0000_0100;
 pub const XL_FS_MODE: u8 = 0b0000_0010;
 pub const LOW_PASS_ON_6D: u8 = 0b0000_0001;
 
@@ -373,6 +742,27 @@ pub enum AccBandwidth {
     HighOdr800 = 0b1110_0100,
 }
 
+impl AccBandwidth {
+    /// Whether the bandwidth selects the high-pass path, HP_SLOPE_XL_EN
+    pub fn is_high_pass(self) -> bool {
+        self as u8 & HP_SLOPE_XL_EN != 0
+    }
+
+    /// Cutoff frequency as a fraction of the ODR, ODR/n
+    pub fn odr_divider(self) -> f32 {
+        match (self as u8 & HPCF_XL_MASK) >> 5 {
+            0 => 4.0,
+            1 => 10.0,
+            2 => 20.0,
+            3 => 45.0,
+            4 => 100.0,
+            5 => 200.0,
+            6 => 400.0,
+            _ => 800.0,
+        }
+    }
+}
+
 /// === CTRL9_XL (18h) ===
 pub const DEN_X: u8 = 0b1000_0000;
 pub const DEN_Y: u8 = 0b0100_0000;
@@ -393,3 +783,397 @@ pub const DOUBLE_TAP: u8 = 0b0000_1000;
 pub const SINGLE_TAP: u8 = 0b0000_0100;
 pub const WU_IA: u8 = 0b0000_0010;
 pub const FF_IA: u8 = 0b0000_0001;
+
+/// === WAKE_UP_SRC (1Bh) ===
+pub const SLEEP_CHANGE_SRC: u8 = 0b0100_0000;
+pub const FF_SRC: u8 = 0b0010_0000;
+pub const SLEEP_STATE: u8 = 0b0001_0000;
+pub const WU_SRC: u8 = 0b0000_1000;
+pub const X_WU: u8 = 0b0000_0100;
+pub const Y_WU: u8 = 0b0000_0010;
+pub const Z_WU: u8 = 0b0000_0001;
+
+/// === TAP_SRC (1Ch) ===
+pub const TAP_IA: u8 = 0b0100_0000;
+pub const SINGLE_TAP_SRC: u8 = 0b0010_0000;
+pub const DOUBLE_TAP_SRC: u8 = 0b0001_0000;
+pub const TAP_SIGN: u8 = 0b0000_1000;
+pub const X_TAP: u8 = 0b0000_0100;
+pub const Y_TAP: u8 = 0b0000_0010;
+pub const Z_TAP: u8 = 0b0000_0001;
+
+/// === D6D_SRC (1Dh) ===
+pub const DEN_DRDY: u8 = 0b1000_0000;
+pub const D6D_SRC_IA: u8 = 0b0100_0000;
+pub const ZH: u8 = 0b0010_0000;
+pub const ZL: u8 = 0b0001_0000;
+pub const YH: u8 = 0b0000_1000;
+pub const YL: u8 = 0b0000_0100;
+pub const XH: u8 = 0b0000_0010;
+pub const XL: u8 = 0b0000_0001;
+
+/// === STATUS_REG (1Eh) ===
+pub const TDA: u8 = 0b0000_0100;
+pub const GDA: u8 = 0b0000_0010;
+pub const XLDA: u8 = 0b0000_0001;
+
+/// === STATUS_SPIAux (1Eh), auxiliary SPI ===
+pub const GYRO_SETTLING: u8 = 0b0000_0100;
+
+/// === FIFO_STATUS2 (3Bh) ===
+pub const FIFO_WTM_IA: u8 = 0b1000_0000;
+pub const FIFO_OVR_IA: u8 = 0b0100_0000;
+pub const FIFO_FULL_IA: u8 = 0b0010_0000;
+pub const COUNTER_BDR_IA: u8 = 0b0001_0000;
+pub const FIFO_OVR_LATCHED: u8 = 0b0000_1000;
+pub const DIFF_FIFO_H_MASK: u8 = 0b0000_0011;
+
+/// === INT_OIS (6Fh) ===
+/// INT_OIS and CTRL1_OIS..CTRL3_OIS are read-only from the primary interface
+pub const INT2_DRDY_OIS: u8 = 0b1000_0000;
+pub const LVL2_OIS: u8 = 0b0100_0000;
+pub const DEN_LH_OIS: u8 = 0b0010_0000;
+pub const ST_XL_OIS_MASK: u8 = 0b0000_0011;
+
+/// === CTRL1_OIS (70h) ===
+pub const LVL1_OIS: u8 = 0b0100_0000;
+pub const SIM_OIS: u8 = 0b0010_0000;
+pub const MODE4_EN: u8 = 0b0001_0000;
+/// Same layout as FS_G and FS_125 in CTRL2_G, see [`GyrScale`]
+pub const FS_G_OIS_MASK: u8 = 0b0000_1110;
+pub const OIS_EN_SPI2: u8 = 0b0000_0001;
+
+/// === CTRL2_OIS (71h) ===
+/// Same layout as HPM_G in CTRL7_G, see [`GyroHpfCutoff`]
+pub const HPM_OIS_MASK: u8 = 0b0011_0000;
+pub const FTYPE_OIS_MASK: u8 = 0b0000_0110;
+pub const HP_EN_OIS: u8 = 0b0000_0001;
+
+/// Gyroscope OIS chain low-pass filter cutoff
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum OisGyrLpf {
+    /// 297 Hz
+    Hz297 = 0b0000_0000,
+    /// 222 Hz
+    Hz222 = 0b0000_0010,
+    /// 154 Hz
+    Hz154 = 0b0000_0100,
+    /// 470 Hz
+    Hz470 = 0b0000_0110,
+}
+
+/// === CTRL3_OIS (72h) ===
+pub const FS_XL_OIS_MASK: u8 = 0b1100_0000;
+/// FS_XL_OIS sits 4 bits above FS_XL in CTRL1_XL, see [`AccScale`]
+pub const FS_XL_OIS_SHIFT: u8 = 4;
+pub const FILTER_XL_CONF_OIS_MASK: u8 = 0b0011_1000;
+pub const ST_OIS_MASK: u8 = 0b0000_0110;
+pub const ST_OIS_CLAMPDIS: u8 = 0b0000_0001;
+
+/// Accelerometer OIS chain bandwidth
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum OisAccBandwidth {
+    /// 289 Hz
+    Hz289 = 0b0000_0000,
+    /// 258 Hz
+    Hz258 = 0b0000_1000,
+    /// 120 Hz
+    Hz120 = 0b0001_0000,
+    /// 65.2 Hz
+    Hz65_2 = 0b0001_1000,
+    /// 33.2 Hz
+    Hz33_2 = 0b0010_0000,
+    /// 16.6 Hz
+    Hz16_6 = 0b0010_1000,
+    /// 8.3 Hz
+    Hz8_3 = 0b0011_0000,
+    /// 4.11 Hz
+    Hz4_11 = 0b0011_1000,
+}
+
+/// === X_OFS_USR (73h), Y_OFS_USR (74h), Z_OFS_USR (75h) ===
+pub const OFS_USR_MAX: f32 = 127.0;
+/// Offset weight in mg/LSB, 2^-10 g with USR_OFF_W = 0 and 2^-6 g with 1
+pub const OFS_USR_FINE_MG: f32 = 0.976_562_5;
+pub const OFS_USR_COARSE_MG: f32 = 15.625;
+
+/// === FIFO_DATA_OUT_TAG (78h) ===
+pub const TAG_SENSOR_SHIFT: u8 = 3;
+pub const TAG_CNT_MASK: u8 = 0b0000_0110;
+pub const TAG_PARITY: u8 = 0b0000_0001;
+
+/// Bytes per FIFO word, tag followed by six data bytes
+pub const FIFO_WORD_LEN: usize = 7;
+
+/// FIFO tag sensor identifiers
+pub const TAG_GYRO_NC: u8 = 0x01;
+pub const TAG_ACCEL_NC: u8 = 0x02;
+pub const TAG_TEMPERATURE: u8 = 0x03;
+pub const TAG_TIMESTAMP: u8 = 0x04;
+pub const TAG_CFG_CHANGE: u8 = 0x05;
+pub const TAG_SENSORHUB_SLAVE0: u8 = 0x0E;
+pub const TAG_SENSORHUB_SLAVE3: u8 = 0x11;
+
+/// === TIMESTAMP2 (42h) ===
+/// Writing this value resets the timestamp counter
+pub const TIMESTAMP_RESET: u8 = 0xAA;
+/// Nominal timestamp resolution in ns
+pub const TIMESTAMP_LSB_NS: f32 = 25_000.0;
+
+/// === TAP_CFG0 (56h) ===
+pub const INT_CLR_ON_READ: u8 = 0b0100_0000;
+pub const SLEEP_STATUS_ON_INT: u8 = 0b0010_0000;
+pub const SLOPE_FDS: u8 = 0b0001_0000;
+pub const TAP_X_EN: u8 = 0b0000_1000;
+pub const TAP_Y_EN: u8 = 0b0000_0100;
+pub const TAP_Z_EN: u8 = 0b0000_0010;
+pub const LIR: u8 = 0b0000_0001;
+
+/// === TAP_CFG1 (57h) ===
+pub const TAP_PRIORITY_MASK: u8 = 0b1110_0000;
+/// Tap thresholds are 5 bits in TAP_CFG1, TAP_CFG2 and TAP_THS_6D
+pub const TAP_THS_MASK: u8 = 0b0001_1111;
+/// Tap threshold LSBs per full scale
+pub const TAP_THS_STEPS: f32 = 32.0;
+
+/// Order in which the axes are checked for a tap
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TapPriority {
+    Xyz = 0b0000_0000,
+    Yxz = 0b0010_0000,
+    Xzy = 0b0100_0000,
+    Zyx = 0b0110_0000,
+    Yzx = 0b1010_0000,
+    Zxy = 0b1100_0000,
+}
+
+/// === TAP_CFG2 (58h) ===
+pub const INTERRUPTS_ENABLE: u8 = 0b1000_0000;
+pub const INACT_EN_MASK: u8 = 0b0110_0000;
+
+/// Power state while inactive, the accelerometer drops to 12.5 Hz
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum InactivityMode {
+    /// Activity/inactivity detection disabled
+    Disabled = 0b0000_0000,
+    /// Gyroscope unchanged
+    GyrUnchanged = 0b0010_0000,
+    /// Gyroscope in sleep mode
+    GyrSleep = 0b0100_0000,
+    /// Gyroscope powered down
+    GyrPowerDown = 0b0110_0000,
+}
+
+/// === TAP_THS_6D (59h) ===
+pub const D4D_EN: u8 = 0b1000_0000;
+pub const SIXD_THS_MASK: u8 = 0b0110_0000;
+
+/// 6D/4D orientation threshold angle
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SixdThreshold {
+    /// 80 degrees
+    Deg80 = 0b0000_0000,
+    /// 70 degrees
+    Deg70 = 0b0010_0000,
+    /// 60 degrees
+    Deg60 = 0b0100_0000,
+    /// 50 degrees
+    Deg50 = 0b0110_0000,
+}
+
+/// === INT_DUR2 (5Ah) ===
+pub const DUR_SHIFT: u8 = 4;
+pub const QUIET_SHIFT: u8 = 2;
+pub const DUR_MAX: u8 = 0b1111;
+pub const QUIET_MAX: u8 = 0b11;
+pub const SHOCK_MAX: u8 = 0b11;
+
+/// === WAKE_UP_THS (5Bh) ===
+pub const SINGLE_DOUBLE_TAP: u8 = 0b1000_0000;
+pub const USR_OFF_ON_WU: u8 = 0b0100_0000;
+pub const WK_THS_MASK: u8 = 0b0011_1111;
+/// Wake-up threshold LSBs per full scale, with WAKE_THS_W = 0 and 1
+pub const WK_THS_STEPS: f32 = 64.0;
+pub const WK_THS_FINE_STEPS: f32 = 256.0;
+
+/// === WAKE_UP_DUR (5Ch) ===
+pub const FF_DUR5: u8 = 0b1000_0000;
+pub const WAKE_DUR_SHIFT: u8 = 5;
+pub const WAKE_DUR_MAX: u8 = 0b11;
+pub const WAKE_THS_W: u8 = 0b0001_0000;
+pub const SLEEP_DUR_MASK: u8 = 0b0000_1111;
+
+/// === FREE_FALL (5Dh) ===
+pub const FF_DUR_SHIFT: u8 = 3;
+pub const FF_DUR_MAX: u8 = 0b11_1111;
+pub const FF_THS_MASK: u8 = 0b0000_0111;
+
+/// Free-fall threshold
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FreeFallThreshold {
+    /// 156 mg
+    Mg156 = 0b000,
+    /// 219 mg
+    Mg219 = 0b001,
+    /// 250 mg
+    Mg250 = 0b010,
+    /// 312 mg
+    Mg312 = 0b011,
+    /// 344 mg
+    Mg344 = 0b100,
+    /// 406 mg
+    Mg406 = 0b101,
+    /// 469 mg
+    Mg469 = 0b110,
+    /// 500 mg
+    Mg500 = 0b111,
+}
+
+/// === MD1_CFG (5Eh) ===
+pub const INT1_SLEEP_CHANGE: u8 = 0b1000_0000;
+pub const INT1_SINGLE_TAP: u8 = 0b0100_0000;
+pub const INT1_WU: u8 = 0b0010_0000;
+pub const INT1_FF: u8 = 0b0001_0000;
+pub const INT1_DOUBLE_TAP: u8 = 0b0000_1000;
+pub const INT1_6D: u8 = 0b0000_0100;
+pub const INT1_EMB_FUNC: u8 = 0b0000_0010;
+pub const INT1_SHUB: u8 = 0b0000_0001;
+
+/// === MD2_CFG (5Fh) ===
+pub const INT2_SLEEP_CHANGE: u8 = 0b1000_0000;
+pub const INT2_SINGLE_TAP: u8 = 0b0100_0000;
+pub const INT2_WU: u8 = 0b0010_0000;
+pub const INT2_FF: u8 = 0b0001_0000;
+pub const INT2_DOUBLE_TAP: u8 = 0b0000_1000;
+pub const INT2_6D: u8 = 0b0000_0100;
+pub const INT2_EMB_FUNC: u8 = 0b0000_0010;
+pub const INT2_TIMESTAMP: u8 = 0b0000_0001;
+
+/// === INTERNAL_FREQ_FINE (63h) ===
+/// Relative deviation of the internal clock per INTERNAL_FREQ_FINE LSB
+pub const FREQ_FINE_STEP: f32 = 0.0015;
+
+// ===== Embedded functions bank =====
+
+/// === EMB_FUNC_EN_A (04h) ===
+pub const SIGN_MOTION_EN: u8 = 0b0010_0000;
+pub const TILT_EN: u8 = 0b0001_0000;
+pub const PEDO_EN: u8 = 0b0000_1000;
+
+/// === EMB_FUNC_EN_B (05h) ===
+pub const PEDO_ADV_EN: u8 = 0b0001_0000;
+pub const FIFO_COMPR_EN: u8 = 0b0000_1000;
+pub const FSM_EN: u8 = 0b0000_0001;
+
+/// === EMB_FUNC_INT1 (0Ah), EMB_FUNC_INT2 (0Eh) ===
+pub const INT_FSM_LC: u8 = 0b1000_0000;
+pub const INT_SIG_MOT: u8 = 0b0010_0000;
+pub const INT_TILT: u8 = 0b0001_0000;
+pub const INT_STEP_DETECTOR: u8 = 0b0000_1000;
+
+/// === EMB_FUNC_STATUS (12h), EMB_FUNC_STATUS_MAINPAGE (35h) ===
+pub const IS_FSM_LC: u8 = 0b1000_0000;
+pub const IS_SIGMOT: u8 = 0b0010_0000;
+pub const IS_TILT: u8 = 0b0001_0000;
+pub const IS_STEP_DET: u8 = 0b0000_1000;
+
+/// === PAGE_SEL (02h) ===
+pub const PAGE_SEL_SHIFT: u8 = 4;
+/// PAGE_SEL bit 0 must stay set
+pub const PAGE_SEL_DEFAULT: u8 = 0b0000_0001;
+
+/// Advanced embedded functions page 1, accessed through PAGE_ADDRESS
+pub const FSM_LC_TIMEOUT_L: u16 = 0x17A;
+
+/// === PAGE_RW (17h) ===
+pub const EMB_FUNC_LIR: u8 = 0b1000_0000;
+pub const PAGE_WRITE: u8 = 0b0100_0000;
+pub const PAGE_READ: u8 = 0b0010_0000;
+
+/// === FSM_LONG_COUNTER_CLEAR (4Ah) ===
+pub const FSM_LC_CLEARED: u8 = 0b0000_0010;
+pub const FSM_LC_CLEAR: u8 = 0b0000_0001;
+
+/// Number of finite state machine slots
+pub const FSM_SLOTS: usize = 16;
+
+/// === EMB_FUNC_ODR_CFG_B (5Fh) ===
+pub const FSM_ODR_MASK: u8 = 0b0001_1000;
+
+/// Finite state machine output data rate
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FsmOdr {
+    /// 12.5 Hz
+    Hz12_5 = 0b0000_0000,
+    /// 26 Hz
+    Hz26 = 0b0000_1000,
+    /// 52 Hz
+    Hz52 = 0b0001_0000,
+    /// 104 Hz
+    Hz104 = 0b0001_1000,
+}
+
+/// === EMB_FUNC_SRC (64h) ===
+pub const PEDO_RST_STEP: u8 = 0b1000_0000;
+pub const STEP_DETECTED: u8 = 0b0010_0000;
+pub const STEP_COUNT_DELTA_IA: u8 = 0b0001_0000;
+pub const STEP_OVERFLOW: u8 = 0b0000_1000;
+pub const STEPCOUNTER_BIT_SET: u8 = 0b0000_0100;
+
+// ===== Sensor hub bank =====
+
+/// Sensor hub output registers, SENSOR_HUB_1 (02h) .. SENSOR_HUB_18 (13h)
+pub const SENSOR_HUB_LEN: usize = 18;
+/// Number of sensor hub slaves
+pub const SHUB_SLAVES: usize = 4;
+/// Registers per slave, SLVx_ADD, SLVx_SUBADD and SLVx_CONFIG
+pub const SLV_REG_STRIDE: u8 = 3;
+
+/// === MASTER_CONFIG (14h) ===
+pub const RST_MASTER_REGS: u8 = 0b1000_0000;
+pub const WRITE_ONCE: u8 = 0b0100_0000;
+pub const START_CONFIG: u8 = 0b0010_0000;
+pub const PASS_THROUGH_MODE: u8 = 0b0001_0000;
+pub const SHUB_PU_EN: u8 = 0b0000_1000;
+pub const MASTER_ON: u8 = 0b0000_0100;
+pub const AUX_SENS_ON_MASK: u8 = 0b0000_0011;
+
+/// === SLVx_ADD (15h, 18h, 1Bh, 1Eh) ===
+pub const SLV_ADD_SHIFT: u8 = 1;
+pub const RW_READ: u8 = 0b0000_0001;
+
+/// === SLVx_CONFIG (17h, 1Ah, 1Dh, 20h) ===
+pub const BATCH_EXT_SENS_EN: u8 = 0b0000_1000;
+pub const SLAVE_NUMOP_MASK: u8 = 0b0000_0111;
+/// SLV0_CONFIG only
+pub const SHUB_ODR_MASK: u8 = 0b1100_0000;
+
+/// Sensor hub read rate, capped by the accelerometer ODR
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ShubOdr {
+    /// 104 Hz
+    Hz104 = 0b0000_0000,
+    /// 52 Hz
+    Hz52 = 0b0100_0000,
+    /// 26 Hz
+    Hz26 = 0b1000_0000,
+    /// 12.5 Hz
+    Hz12_5 = 0b1100_0000,
+}
+
+/// === STATUS_MASTER (22h), STATUS_MASTER_MAINPAGE (39h) ===
+pub const WR_ONCE_DONE: u8 = 0b1000_0000;
+pub const SLAVE3_NACK: u8 = 0b0100_0000;
+pub const SLAVE2_NACK: u8 = 0b0010_0000;
+pub const SLAVE1_NACK: u8 = 0b0001_0000;
+pub const SLAVE0_NACK: u8 = 0b0000_1000;
+pub const SLAVE_NACK_SHIFT: u8 = 3;
+pub const SENS_HUB_ENDOP: u8 = 0b0000_0001;
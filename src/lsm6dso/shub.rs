@@ -0,0 +1,352 @@
+use super::reg::{self, MemoryBank, SensorHubRegister, ShubOdr};
+use super::{update_verified, with_bank, Error, Lsm6dso, Register};
+use crate::lis2mdl::reg as lis2mdl_reg;
+use crate::lis2mdl::Lis2mdlConfig;
+use crate::measurement::MagneticField;
+use crate::register::RegisterBus;
+use crate::transport::Transport;
+use embassy_time::{Duration, Timer};
+
+/// STATUS_MASTER polls while waiting for a write-once to complete, one per
+/// millisecond, enough for a sensor hub cycle at 12.5 Hz
+const WRITE_ONCE_POLLS: u32 = 100;
+
+/// LIS2MDL bytes read per sample, OUTX_L_REG (68h) .. OUTZ_H_REG (6Dh)
+const LIS2MDL_SAMPLE_LEN: usize = 6;
+
+/// Register block read from an external sensor on every sensor hub cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SensorHubRead {
+    address: u8,
+    register: u8,
+    len: u8,
+    batch: bool,
+}
+
+impl SensorHubRead {
+    /// Read `len` registers from `register` of the sensor at the 7-bit I2C
+    /// `address`, `len` saturated at 1..=7
+    #[must_use]
+    pub fn new(address: u8, register: u8, len: u8) -> Self {
+        Self {
+            address,
+            register,
+            len: len.clamp(1, reg::SLAVE_NUMOP_MASK),
+            batch: false,
+        }
+    }
+
+    /// Batch the bytes read in the FIFO, tagged with the slave number
+    #[must_use]
+    pub fn batch(mut self, enable: bool) -> Self {
+        self.batch = enable;
+        self
+    }
+
+    /// SLVx_ADD value
+    pub fn slv_add(&self) -> u8 {
+        self.address << reg::SLV_ADD_SHIFT | reg::RW_READ
+    }
+
+    /// SLVx_CONFIG value, without the SHUB_ODR bits of SLV0_CONFIG
+    pub fn slv_config(&self) -> u8 {
+        let mut reg = self.len;
+        if self.batch {
+            reg |= reg::BATCH_EXT_SENS_EN;
+        }
+        reg
+    }
+}
+
+/// Sensor hub flags decoded from STATUS_MASTER
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SensorHubStatus {
+    /// The last sensor hub cycle completed
+    pub end_of_operation: bool,
+    /// The slave 0 write-once completed
+    pub write_once_done: bool,
+    /// Slaves that did not acknowledge, bit n for slave n
+    pub nack: u8,
+}
+
+impl SensorHubStatus {
+    /// Decode a STATUS_MASTER value
+    pub fn from_status(status: u8) -> Self {
+        Self {
+            end_of_operation: status & reg::SENS_HUB_ENDOP != 0,
+            write_once_done: status & reg::WR_ONCE_DONE != 0,
+            nack: (status
+                & (reg::SLAVE0_NACK | reg::SLAVE1_NACK | reg::SLAVE2_NACK | reg::SLAVE3_NACK))
+                >> reg::SLAVE_NACK_SHIFT,
+        }
+    }
+}
+
+impl<T: Transport> Lsm6dso<T> {
+    /// Configure the sensor hub to read the given register blocks, in slave
+    /// order, and start the I2C master. Reads beyond the fourth are ignored
+    /// and an empty list stops the master. The sensor hub is clocked by the
+    /// accelerometer, which must be running at least at `odr`.
+    pub async fn configure_sensor_hub(
+        &self,
+        reads: &[SensorHubRead],
+        odr: ShubOdr,
+        pull_up: bool,
+    ) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let reads = &reads[..reads.len().min(reg::SHUB_SLAVES)];
+        with_bank(&mut regs, MemoryBank::SensorHub, async |regs| {
+            // The slave registers are only changed with the master off
+            regs.modify_reg(SensorHubRegister::MASTER_CONFIG, reg::MASTER_ON, 0)
+                .await
+                .map_err(Error::Bus)?;
+            let Some(last) = reads.len().checked_sub(1) else {
+                return Ok(());
+            };
+
+            for (slave, read) in reads.iter().enumerate() {
+                let mut config = read.slv_config();
+                if slave == 0 {
+                    config |= odr as u8;
+                }
+                // SLVx_ADD, SLVx_SUBADD, SLVx_CONFIG
+                let first = SensorHubRegister::SLV0_ADD as u8 + slave as u8 * reg::SLV_REG_STRIDE;
+                for (offset, value) in [read.slv_add(), read.register, config]
+                    .into_iter()
+                    .enumerate()
+                {
+                    regs.write_reg(first + offset as u8, value)
+                        .await
+                        .map_err(Error::Bus)?;
+                }
+            }
+
+            let mut master = reg::MASTER_ON | last as u8;
+            if pull_up {
+                master |= reg::SHUB_PU_EN;
+            }
+            regs.modify_reg(
+                SensorHubRegister::MASTER_CONFIG,
+                reg::MASTER_ON | reg::SHUB_PU_EN | reg::AUX_SENS_ON_MASK,
+                master,
+            )
+            .await
+            .map_err(Error::Bus)?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Stop the sensor hub I2C master
+    pub async fn stop_sensor_hub(&self) -> Result<(), Error<T::Error>> {
+        self.configure_sensor_hub(&[], ShubOdr::Hz104, false).await
+    }
+
+    /// Write `value` to `register` of the sensor at the 7-bit I2C `address`
+    /// through slave 0, and wait for the write to complete. The slave 0
+    /// address and register are overwritten, so external sensors are set up
+    /// before [`configure_sensor_hub`](Self::configure_sensor_hub); its read
+    /// length and batching are suspended for the write and restored. The
+    /// accelerometer must be running to clock the sensor hub.
+    pub async fn sensor_hub_write(
+        &self,
+        address: u8,
+        register: u8,
+        value: u8,
+    ) -> Result<(), Error<T::Error>> {
+        // SLV0_CONFIG bits of a read configuration, which would make the
+        // write cycle also read and batch slave 0
+        const SLV0_READ_MASK: u8 = reg::BATCH_EXT_SENS_EN | reg::SLAVE_NUMOP_MASK;
+        let slv0_read = {
+            let mut bus = self.bus.lock().await;
+            let mut regs =
+                RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+            with_bank(&mut regs, MemoryBank::SensorHub, async |regs| {
+                regs.modify_reg(SensorHubRegister::MASTER_CONFIG, reg::MASTER_ON, 0)
+                    .await
+                    .map_err(Error::Bus)?;
+                let slv0_config = regs
+                    .read_reg(SensorHubRegister::SLV0_CONFIG)
+                    .await
+                    .map_err(Error::Bus)?;
+                regs.modify_reg(SensorHubRegister::SLV0_CONFIG, SLV0_READ_MASK, 0)
+                    .await
+                    .map_err(Error::Bus)?;
+                regs.write_reg(SensorHubRegister::SLV0_ADD, address << reg::SLV_ADD_SHIFT)
+                    .await
+                    .map_err(Error::Bus)?;
+                regs.write_reg(SensorHubRegister::SLV0_SUBADD, register)
+                    .await
+                    .map_err(Error::Bus)?;
+                regs.write_reg(SensorHubRegister::DATAWRITE_SLV0, value)
+                    .await
+                    .map_err(Error::Bus)?;
+                regs.modify_reg(
+                    SensorHubRegister::MASTER_CONFIG,
+                    reg::WRITE_ONCE | reg::MASTER_ON | reg::AUX_SENS_ON_MASK,
+                    reg::WRITE_ONCE | reg::MASTER_ON,
+                )
+                .await
+                .map_err(Error::Bus)?;
+                Ok(slv0_config & SLV0_READ_MASK)
+            })
+            .await?
+        };
+
+        // The bus is released between polls so other drivers are not held
+        // off for the whole write
+        let mut result = Err(Error::SensorHubTimeout);
+        for _ in 0..WRITE_ONCE_POLLS {
+            let status = {
+                let mut bus = self.bus.lock().await;
+                match RegisterBus::new(&mut bus, &self.transport) {
+                    Some(mut regs) => regs
+                        .read_reg(Register::STATUS_MASTER_MAINPAGE)
+                        .await
+                        .map_err(Error::Bus),
+                    None => Err(Error::BusNotInitialized),
+                }
+            };
+            match status {
+                Err(error) => {
+                    result = Err(error);
+                    break;
+                }
+                Ok(status) if status & reg::SLAVE0_NACK != 0 => {
+                    result = Err(Error::SensorHubNack);
+                    break;
+                }
+                Ok(status) if status & reg::WR_ONCE_DONE != 0 => {
+                    result = Ok(());
+                    break;
+                }
+                Ok(_) => Timer::after(Duration::from_millis(1)).await,
+            }
+        }
+
+        // The master is stopped and slave 0 restored even when polling failed
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+        with_bank(&mut regs, MemoryBank::SensorHub, async |regs| {
+            regs.modify_reg(SensorHubRegister::MASTER_CONFIG, reg::MASTER_ON, 0)
+                .await
+                .map_err(Error::Bus)?;
+            regs.modify_reg(SensorHubRegister::SLV0_CONFIG, SLV0_READ_MASK, slv0_read)
+                .await
+                .map_err(Error::Bus)
+        })
+        .await?;
+        result
+    }
+
+    /// Read the sensor hub output registers from SENSOR_HUB_1, holding the
+    /// bytes of each slave in slave order. At most 18 bytes are read.
+    pub async fn read_sensor_hub(&self, data: &mut [u8]) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let len = data.len().min(reg::SENSOR_HUB_LEN);
+        with_bank(&mut regs, MemoryBank::SensorHub, async |regs| {
+            regs.read_regs(SensorHubRegister::SENSOR_HUB_1, &mut data[..len])
+                .await
+                .map_err(Error::Bus)
+        })
+        .await
+    }
+
+    /// Read the sensor hub flags from the main page copy of STATUS_MASTER
+    pub async fn sensor_hub_status(&self) -> Result<SensorHubStatus, Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let status = regs
+            .read_reg(Register::STATUS_MASTER_MAINPAGE)
+            .await
+            .map_err(Error::Bus)?;
+        Ok(SensorHubStatus::from_status(status))
+    }
+
+    /// Route the sensor hub end of operation to INT1, the only pin it can
+    /// be routed to
+    pub async fn route_sensor_hub(&self, enable: bool) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let mask = reg::INT1_SHUB;
+        update_verified(
+            &mut regs,
+            Register::MD1_CFG,
+            mask,
+            if enable { mask } else { 0 },
+        )
+        .await
+    }
+
+    /// LIS2MDL attached to the sensor hub I2C master
+    pub fn lis2mdl(&self) -> HubLis2mdl<'_, T> {
+        HubLis2mdl { imu: self }
+    }
+}
+
+/// LIS2MDL driven through the LSM6DSO sensor hub. Its samples are read on
+/// every sensor hub cycle and can be batched in the LSM6DSO FIFO, aligned
+/// with the accelerometer and gyroscope data.
+pub struct HubLis2mdl<'a, T: Transport> {
+    imu: &'a Lsm6dso<T>,
+}
+
+impl<T: Transport> HubLis2mdl<'_, T> {
+    /// Write the LIS2MDL configuration registers through the sensor hub.
+    /// Writes cannot be verified by read-back and the whole of each
+    /// register is written.
+    pub async fn init(&self, config: &Lis2mdlConfig) -> Result<(), Error<T::Error>> {
+        for (register, value) in [
+            (lis2mdl_reg::Register::CFG_REG_A, config.cfg_reg_a()),
+            (lis2mdl_reg::Register::CFG_REG_B, config.cfg_reg_b()),
+            (lis2mdl_reg::Register::CFG_REG_C, config.cfg_reg_c()),
+        ] {
+            self.imu
+                .sensor_hub_write(lis2mdl_reg::I2C_SAD, register.addr(), value)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Read the LIS2MDL output registers as slave 0 on every sensor hub
+    /// cycle, replacing any other sensor hub configuration
+    pub async fn start(&self, odr: ShubOdr, batch: bool) -> Result<(), Error<T::Error>> {
+        let read = SensorHubRead::new(
+            lis2mdl_reg::I2C_SAD,
+            lis2mdl_reg::Register::OUT_X_L.addr(),
+            LIS2MDL_SAMPLE_LEN as u8,
+        )
+        .batch(batch);
+        self.imu.configure_sensor_hub(&[read], odr, false).await
+    }
+
+    /// Read the last magnetic field collected by the sensor hub
+    pub async fn magnetic_field(&self) -> Result<MagneticField, Error<T::Error>> {
+        let mut data = [0u8; LIS2MDL_SAMPLE_LEN];
+        self.imu.read_sensor_hub(&mut data).await?;
+        Ok(Self::decode(data))
+    }
+
+    /// Decode LIS2MDL output bytes, such as the data of a batched
+    /// [`FifoSample::SensorHub`](super::FifoSample::SensorHub) word
+    pub fn decode(data: [u8; LIS2MDL_SAMPLE_LEN]) -> MagneticField {
+        let [x, y, z] = [0, 2, 4].map(|i| {
+            lis2mdl_reg::GAUSS_PER_LSB * f32::from(i16::from_le_bytes([data[i], data[i + 1]]))
+        });
+        MagneticField { x, y, z }
+    }
+}
@@ -0,0 +1,337 @@
+use super::reg::{self, AccScale, TapPriority};
+use super::{update_verified, wait_for_event, Error, InterruptPin, Lsm6dso, Register};
+use crate::register::RegisterBus;
+use crate::transport::Transport;
+use embedded_hal_async::digital::Wait;
+
+/// LSM6DSO single and double tap configuration
+///
+/// Windows are counted in accelerometer ODR periods, see the field setters.
+/// The default configuration detects single and double taps on all axes
+/// above 0.5 g with the widest windows, and latches the events until
+/// TAP_SRC is read. The accelerometer ODR should be at least 416 Hz.
+#[derive(Copy, Clone)]
+pub struct TapConfig {
+    x: bool,
+    y: bool,
+    z: bool,
+    threshold: f32,
+    priority: TapPriority,
+    shock: u8,
+    quiet: u8,
+    duration: u8,
+    double_tap: bool,
+    latched: bool,
+}
+
+impl Default for TapConfig {
+    fn default() -> Self {
+        Self {
+            x: true,
+            y: true,
+            z: true,
+            threshold: 0.5,
+            priority: TapPriority::Xyz,
+            shock: reg::SHOCK_MAX,
+            quiet: reg::QUIET_MAX,
+            duration: reg::DUR_MAX,
+            double_tap: true,
+            latched: true,
+        }
+    }
+}
+
+impl TapConfig {
+    /// TAP_CFG0 bits owned by the configuration
+    pub(crate) const TAP_CFG0_MASK: u8 =
+        reg::TAP_X_EN | reg::TAP_Y_EN | reg::TAP_Z_EN | reg::LIR | reg::INT_CLR_ON_READ;
+    /// TAP_CFG2 bits owned by the configuration
+    pub(crate) const TAP_CFG2_MASK: u8 = reg::INTERRUPTS_ENABLE | reg::TAP_THS_MASK;
+
+    /// Enable tap detection on each axis
+    #[must_use]
+    pub fn axes(mut self, x: bool, y: bool, z: bool) -> Self {
+        self.x = x;
+        self.y = y;
+        self.z = z;
+        self
+    }
+
+    /// Tap threshold in g, applied to all axes. The resolution is 1/32 of
+    /// the accelerometer full scale.
+    #[must_use]
+    pub fn threshold(mut self, g: f32) -> Self {
+        self.threshold = g;
+        self
+    }
+
+    /// Order in which the axes are checked when several exceed the threshold
+    #[must_use]
+    pub fn priority(mut self, priority: TapPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Maximum duration of a tap above the threshold, 8 ODR periods per
+    /// step or 4 when zero, saturated at 3
+    #[must_use]
+    pub fn shock(mut self, steps: u8) -> Self {
+        self.shock = steps.min(reg::SHOCK_MAX);
+        self
+    }
+
+    /// Quiet time after a tap, 4 ODR periods per step or 2 when zero,
+    /// saturated at 3
+    #[must_use]
+    pub fn quiet(mut self, steps: u8) -> Self {
+        self.quiet = steps.min(reg::QUIET_MAX);
+        self
+    }
+
+    /// Maximum time between the taps of a double tap, 32 ODR periods per
+    /// step or 16 when zero, saturated at 15
+    #[must_use]
+    pub fn duration(mut self, steps: u8) -> Self {
+        self.duration = steps.min(reg::DUR_MAX);
+        self
+    }
+
+    /// Detect double taps as well as single taps
+    #[must_use]
+    pub fn double_tap(mut self, enable: bool) -> Self {
+        self.double_tap = enable;
+        self
+    }
+
+    /// Latch events until their source register is read, which clears them
+    /// at once instead of at the end of the ODR period. Applies to all basic
+    /// interrupts such as wake-up and free-fall.
+    #[must_use]
+    pub fn latched(mut self, enable: bool) -> Self {
+        self.latched = enable;
+        self
+    }
+
+    /// Threshold in LSBs of the given accelerometer full scale
+    fn threshold_lsb(&self, scale: AccScale) -> u8 {
+        let lsb = self.threshold / scale.full_scale_g() * reg::TAP_THS_STEPS + 0.5;
+        (lsb as u8).min(reg::TAP_THS_MASK)
+    }
+
+    /// TAP_CFG0 (56h) value
+    pub fn tap_cfg0(&self) -> u8 {
+        let mut reg = 0;
+        if self.x {
+            reg |= reg::TAP_X_EN;
+        }
+        if self.y {
+            reg |= reg::TAP_Y_EN;
+        }
+        if self.z {
+            reg |= reg::TAP_Z_EN;
+        }
+        if self.latched {
+            reg |= reg::LIR | reg::INT_CLR_ON_READ;
+        }
+        reg
+    }
+
+    /// TAP_CFG1 (57h) value for the given accelerometer full scale
+    pub fn tap_cfg1(&self, scale: AccScale) -> u8 {
+        self.priority as u8 | self.threshold_lsb(scale)
+    }
+
+    /// TAP_CFG2 (58h) value for the given accelerometer full scale
+    pub fn tap_cfg2(&self, scale: AccScale) -> u8 {
+        reg::INTERRUPTS_ENABLE | self.threshold_lsb(scale)
+    }
+
+    /// TAP_THS_6D (59h) value for the given accelerometer full scale
+    pub fn tap_ths_6d(&self, scale: AccScale) -> u8 {
+        self.threshold_lsb(scale)
+    }
+
+    /// INT_DUR2 (5Ah) value
+    pub fn int_dur2(&self) -> u8 {
+        self.duration << reg::DUR_SHIFT | self.quiet << reg::QUIET_SHIFT | self.shock
+    }
+
+    /// WAKE_UP_THS (5Bh) value
+    pub fn wake_up_ths(&self) -> u8 {
+        if self.double_tap {
+            reg::SINGLE_DOUBLE_TAP
+        } else {
+            0
+        }
+    }
+}
+
+/// Accelerometer axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+/// Tap event kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TapKind {
+    Single,
+    Double,
+}
+
+/// Tap event decoded from TAP_SRC
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TapEvent {
+    pub kind: TapKind,
+    /// First axis the tap was detected on
+    pub axis: Axis,
+    /// The tap accelerated the device towards the negative axis
+    pub negative: bool,
+}
+
+impl TapEvent {
+    /// Decode a TAP_SRC value, `None` when no tap was detected
+    pub fn from_src(src: u8) -> Option<Self> {
+        if src & reg::TAP_IA == 0 {
+            return None;
+        }
+        let kind = if src & reg::DOUBLE_TAP_SRC != 0 {
+            TapKind::Double
+        } else if src & reg::SINGLE_TAP_SRC != 0 {
+            TapKind::Single
+        } else {
+            return None;
+        };
+        let axis = if src & reg::X_TAP != 0 {
+            Axis::X
+        } else if src & reg::Y_TAP != 0 {
+            Axis::Y
+        } else if src & reg::Z_TAP != 0 {
+            Axis::Z
+        } else {
+            return None;
+        };
+        Some(Self {
+            kind,
+            axis,
+            negative: src & reg::TAP_SIGN != 0,
+        })
+    }
+}
+
+/// Stream of tap events signalled on an interrupt pin, see
+/// [`Lsm6dso::tap_events`]
+pub struct TapEvents<'a, T: Transport, P> {
+    sensor: &'a Lsm6dso<T>,
+    pin: &'a mut P,
+}
+
+impl<T: Transport, P: Wait> TapEvents<'_, T, P> {
+    /// Wait for the next tap event. Pin assertions without a tap in TAP_SRC
+    /// are skipped, so the pin should only carry tap events.
+    pub async fn next(&mut self) -> Result<TapEvent, Error<T::Error>> {
+        let sensor = self.sensor;
+        wait_for_event(self.pin, async || sensor.read_tap_event().await).await
+    }
+}
+
+impl<T: Transport> Lsm6dso<T> {
+    /// Write the tap configuration and verify it by read-back. The threshold
+    /// is converted with the current accelerometer full scale, so configure
+    /// the accelerometer first.
+    pub async fn configure_tap(&self, config: &TapConfig) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        update_verified(
+            &mut regs,
+            Register::TAP_CFG0,
+            TapConfig::TAP_CFG0_MASK,
+            config.tap_cfg0(),
+        )
+        .await?;
+        update_verified(
+            &mut regs,
+            Register::TAP_CFG1,
+            0xFF,
+            config.tap_cfg1(self.acc_scale),
+        )
+        .await?;
+        update_verified(
+            &mut regs,
+            Register::TAP_CFG2,
+            TapConfig::TAP_CFG2_MASK,
+            config.tap_cfg2(self.acc_scale),
+        )
+        .await?;
+        update_verified(
+            &mut regs,
+            Register::TAP_THS_6D,
+            reg::TAP_THS_MASK,
+            config.tap_ths_6d(self.acc_scale),
+        )
+        .await?;
+        update_verified(&mut regs, Register::INT_DUR2, 0xFF, config.int_dur2()).await?;
+        update_verified(
+            &mut regs,
+            Register::WAKE_UP_THS,
+            reg::SINGLE_DOUBLE_TAP,
+            config.wake_up_ths(),
+        )
+        .await
+    }
+
+    /// Route the single and double tap events to `pin`
+    pub async fn route_tap(
+        &self,
+        pin: InterruptPin,
+        single: bool,
+        double: bool,
+    ) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let (register, single_mask, double_mask) = match pin {
+            InterruptPin::Int1 => (
+                Register::MD1_CFG,
+                reg::INT1_SINGLE_TAP,
+                reg::INT1_DOUBLE_TAP,
+            ),
+            InterruptPin::Int2 => (
+                Register::MD2_CFG,
+                reg::INT2_SINGLE_TAP,
+                reg::INT2_DOUBLE_TAP,
+            ),
+        };
+        let mut value = 0;
+        if single {
+            value |= single_mask;
+        }
+        if double {
+            value |= double_mask;
+        }
+        update_verified(&mut regs, register, single_mask | double_mask, value).await
+    }
+
+    /// Read and decode TAP_SRC, clearing a latched tap event
+    pub async fn read_tap_event(&self) -> Result<Option<TapEvent>, Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let src = regs.read_reg(Register::TAP_SRC).await.map_err(Error::Bus)?;
+        Ok(TapEvent::from_src(src))
+    }
+
+    /// Tap events signalled on `pin`, routed with [`route_tap`](Self::route_tap)
+    pub fn tap_events<'a, P: Wait>(&'a self, pin: &'a mut P) -> TapEvents<'a, T, P> {
+        TapEvents { sensor: self, pin }
+    }
+}
@@ -0,0 +1,179 @@
+use super::reg;
+use super::{update_verified, Error, Lsm6dso, Register};
+use crate::register::RegisterBus;
+use crate::transport::Transport;
+use embassy_time::Instant;
+
+/// Fractional bits of the fixed-point tick length
+const FRAC_BITS: u32 = 24;
+/// Shortest sync baseline used to estimate the sensor clock, about 1 s
+const MIN_BASELINE_TICKS: u64 = 40_000;
+
+/// Sync point, a counter value and the MCU instant it was read at
+#[derive(Copy, Clone, Debug)]
+struct SyncPoint {
+    raw: u32,
+    /// Ticks since the first sync point, unwrapped across counter overflows
+    ticks: u64,
+    at: Instant,
+}
+
+/// Maps LSM6DSO timestamp counter values to MCU time
+///
+/// Each sync records a counter value together with the MCU instant it was
+/// read at. Once the sync points span at least a second, the tick length is
+/// estimated from the first and latest points, which absorbs the drift
+/// between the sensor and MCU clocks. Counter values are mapped relative to
+/// the latest sync point, so syncing periodically keeps the error bounded.
+#[derive(Copy, Clone, Debug)]
+pub struct TimestampCorrelator {
+    /// Nominal tick length in us, fixed point
+    nominal: i64,
+    /// Estimated tick length in us, fixed point
+    tick: i64,
+    first: Option<SyncPoint>,
+    last: Option<SyncPoint>,
+}
+
+impl TimestampCorrelator {
+    /// Correlator for a counter with the given nominal resolution, see
+    /// [`Lsm6dso::timestamp_resolution`]
+    pub fn new(resolution_ns: f32) -> Self {
+        let nominal = (resolution_ns / 1000.0 * (1u32 << FRAC_BITS) as f32) as i64;
+        Self {
+            nominal,
+            tick: nominal,
+            first: None,
+            last: None,
+        }
+    }
+
+    /// Drop all sync points, required after resetting the counter
+    pub fn reset(&mut self) {
+        self.tick = self.nominal;
+        self.first = None;
+        self.last = None;
+    }
+
+    /// Record that the counter read `raw` at `at`. Sync points must be less
+    /// than a counter period (about 29 hours) apart.
+    pub fn sync(&mut self, raw: u32, at: Instant) {
+        let ticks = match self.last {
+            Some(last) => last.ticks + u64::from(raw.wrapping_sub(last.raw)),
+            None => 0,
+        };
+        let point = SyncPoint { raw, ticks, at };
+        let first = *self.first.get_or_insert(point);
+        self.last = Some(point);
+
+        if ticks - first.ticks >= MIN_BASELINE_TICKS && at > first.at {
+            // In u128 so baselines longer than 2^40 us, about 12.7 days, do
+            // not overflow the fixed-point shift
+            let span = u128::from((at - first.at).as_micros());
+            self.tick = ((span << FRAC_BITS) / u128::from(ticks - first.ticks)) as i64;
+        }
+    }
+
+    /// MCU instant of the counter value `raw`, `None` before the first sync.
+    /// Values up to half a counter period before or after the latest sync
+    /// point are mapped.
+    pub fn instant(&self, raw: u32) -> Option<Instant> {
+        let last = self.last?;
+        let ticks = i64::from(raw.wrapping_sub(last.raw) as i32);
+        // Rounded to the nearest microsecond
+        let offset = (ticks * self.tick + (1 << (FRAC_BITS - 1))) >> FRAC_BITS;
+        Some(Instant::from_micros(
+            last.at.as_micros().saturating_add_signed(offset),
+        ))
+    }
+
+    /// Estimated sensor clock drift from its nominal rate, in ppm. Positive
+    /// when the sensor counter runs slow.
+    pub fn drift_ppm(&self) -> f32 {
+        (self.tick - self.nominal) as f32 / self.nominal as f32 * 1e6
+    }
+}
+
+impl<T: Transport> Lsm6dso<T> {
+    /// Start or stop the timestamp counter
+    pub async fn enable_timestamp(&self, enable: bool) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let value = if enable { reg::TIMESTAMP_EN } else { 0 };
+        update_verified(&mut regs, Register::CTRL10_C, reg::TIMESTAMP_EN, value).await
+    }
+
+    /// Reset the timestamp counter to zero
+    pub async fn reset_timestamp(&self) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        regs.write_reg(Register::TIMESTAMP2, reg::TIMESTAMP_RESET)
+            .await
+            .map_err(Error::Bus)
+    }
+
+    /// Read the 32-bit timestamp counter
+    pub async fn timestamp(&self) -> Result<u32, Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let mut buffer = [0u8; 4];
+        regs.read_regs(Register::TIMESTAMP0, &mut buffer)
+            .await
+            .map_err(Error::Bus)?;
+        Ok(u32::from_le_bytes(buffer))
+    }
+
+    /// Timestamp counter resolution in ns, corrected with the factory
+    /// trimmed INTERNAL_FREQ_FINE deviation of the internal clock
+    pub async fn timestamp_resolution(&self) -> Result<f32, Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let freq_fine = regs
+            .read_reg(Register::INTERNAL_FREQ_FINE)
+            .await
+            .map_err(Error::Bus)? as i8;
+        Ok(reg::TIMESTAMP_LSB_NS / (1.0 + reg::FREQ_FINE_STEP * f32::from(freq_fine)))
+    }
+
+    /// Whether the timestamp counter is about to overflow
+    pub async fn timestamp_overflow(&self) -> Result<bool, Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let status = regs
+            .read_reg(Register::ALL_INT_SRC)
+            .await
+            .map_err(Error::Bus)?;
+        Ok(status & reg::TIMESTAMP_ENDCOUNT != 0)
+    }
+
+    /// Read the timestamp counter and record it in `correlator`, stamped with
+    /// the midpoint of the bus transfer
+    pub async fn sync_timestamp(
+        &self,
+        correlator: &mut TimestampCorrelator,
+    ) -> Result<(), Error<T::Error>> {
+        let mut bus = self.bus.lock().await;
+        let mut regs =
+            RegisterBus::new(&mut bus, &self.transport).ok_or(Error::BusNotInitialized)?;
+
+        let mut buffer = [0u8; 4];
+        let before = Instant::now();
+        regs.read_regs(Register::TIMESTAMP0, &mut buffer)
+            .await
+            .map_err(Error::Bus)?;
+        let after = Instant::now();
+
+        correlator.sync(u32::from_le_bytes(buffer), before + (after - before) / 2);
+        Ok(())
+    }
+}
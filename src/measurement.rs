@@ -0,0 +1,77 @@
+//! Physical quantities returned by the drivers
+
+/// Magnetic field in gauss
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MagneticField {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl MagneticField {
+    /// Field components in microtesla
+    pub fn microtesla(self) -> [f32; 3] {
+        [self.x * 100.0, self.y * 100.0, self.z * 100.0]
+    }
+}
+
+/// Pressure in pascal
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Pressure(pub f32);
+
+impl Pressure {
+    /// Pressure in hectopascal
+    pub fn hectopascal(self) -> f32 {
+        self.0 / 100.0
+    }
+}
+
+/// Temperature in degrees Celsius
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Temperature(pub f32);
+
+/// Linear acceleration in g
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Acceleration {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Acceleration {
+    /// Standard gravity in m/s²
+    pub const STANDARD_GRAVITY: f32 = 9.806_65;
+
+    /// Acceleration components in m/s²
+    pub fn meters_per_second_squared(self) -> [f32; 3] {
+        [
+            self.x * Self::STANDARD_GRAVITY,
+            self.y * Self::STANDARD_GRAVITY,
+            self.z * Self::STANDARD_GRAVITY,
+        ]
+    }
+}
+
+/// Angular rate in degrees per second
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AngularRate {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl AngularRate {
+    /// Angular rate components in rad/s
+    pub fn radians_per_second(self) -> [f32; 3] {
+        [
+            self.x.to_radians(),
+            self.y.to_radians(),
+            self.z.to_radians(),
+        ]
+    }
+}
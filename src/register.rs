@@ -0,0 +1,70 @@
+//! Register access shared by the drivers
+
+use crate::transport::Transport;
+
+/// Register of a device register map
+pub(crate) trait RegisterAddress: Copy {
+    /// Get register address
+    fn addr(self) -> u8;
+}
+
+/// Raw register address, used to replay register sequences
+impl RegisterAddress for u8 {
+    fn addr(self) -> u8 {
+        self
+    }
+}
+
+/// Register-level access to one device on a locked bus
+pub(crate) struct RegisterBus<'a, T: Transport> {
+    bus: &'a mut T::Bus,
+    transport: &'a T,
+}
+
+impl<'a, T: Transport> RegisterBus<'a, T> {
+    /// Access the device through `transport`, `None` if the shared bus has
+    /// not been initialized yet
+    pub(crate) fn new(bus: &'a mut Option<T::Bus>, transport: &'a T) -> Option<Self> {
+        bus.as_mut().map(|bus| Self { bus, transport })
+    }
+
+    /// Read a single register
+    pub(crate) async fn read_reg<R: RegisterAddress>(&mut self, reg: R) -> Result<u8, T::Error> {
+        let mut data = [0u8; 1];
+        self.read_regs(reg, &mut data).await?;
+        Ok(data[0])
+    }
+
+    /// Burst read consecutive registers starting at `reg`, the device must
+    /// have register address auto-increment enabled
+    pub(crate) async fn read_regs<R: RegisterAddress>(
+        &mut self,
+        reg: R,
+        buf: &mut [u8],
+    ) -> Result<(), T::Error> {
+        self.transport.read(self.bus, reg.addr(), buf).await
+    }
+
+    /// Write a single register
+    pub(crate) async fn write_reg<R: RegisterAddress>(
+        &mut self,
+        reg: R,
+        value: u8,
+    ) -> Result<(), T::Error> {
+        self.transport.write(self.bus, reg.addr(), value).await
+    }
+
+    /// Replace the bits selected by `mask` with those of `value`, leaving the
+    /// other bits untouched. Returns the value written to the register.
+    pub(crate) async fn modify_reg<R: RegisterAddress>(
+        &mut self,
+        reg: R,
+        mask: u8,
+        value: u8,
+    ) -> Result<u8, T::Error> {
+        let current = self.read_reg(reg).await?;
+        let new = (current & !mask) | (value & mask);
+        self.write_reg(reg, new).await?;
+        Ok(new)
+    }
+}
@@ -0,0 +1,83 @@
+//! Register transports over I2C and SPI
+
+use core::marker::PhantomData;
+use embedded_hal_async::i2c::I2c;
+use embedded_hal_async::spi::{Operation, SpiDevice};
+
+/// Read flag of the SPI address byte
+const SPI_READ: u8 = 0b1000_0000;
+
+/// Register-level access to a device over a specific bus
+///
+/// Burst reads rely on the device auto-incrementing the register address:
+/// the LIS2MDL always does, the LSM6DSO and LPS22HH do as long as `IF_INC`
+/// and `IF_ADD_INC` are left set, which the driver configurations ensure.
+#[allow(async_fn_in_trait)]
+pub trait Transport {
+    /// Underlying bus, shared through a [`SharedBus`](crate::SharedBus)
+    type Bus: 'static;
+    /// Bus error
+    type Error;
+
+    /// Read consecutive registers starting at `reg`
+    async fn read(&self, bus: &mut Self::Bus, reg: u8, buf: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Write a single register
+    async fn write(&self, bus: &mut Self::Bus, reg: u8, value: u8) -> Result<(), Self::Error>;
+}
+
+/// I2C transport to the device at a 7-bit address
+pub struct I2cTransport<I2C> {
+    address: u8,
+    _bus: PhantomData<I2C>,
+}
+
+impl<I2C> I2cTransport<I2C> {
+    pub(crate) fn new(address: u8) -> Self {
+        Self {
+            address,
+            _bus: PhantomData,
+        }
+    }
+}
+
+impl<I2C: I2c + 'static> Transport for I2cTransport<I2C> {
+    type Bus = I2C;
+    type Error = I2C::Error;
+
+    async fn read(&self, bus: &mut I2C, reg: u8, buf: &mut [u8]) -> Result<(), I2C::Error> {
+        bus.write_read(self.address, &[reg], buf).await
+    }
+
+    async fn write(&self, bus: &mut I2C, reg: u8, value: u8) -> Result<(), I2C::Error> {
+        bus.write(self.address, &[reg, value]).await
+    }
+}
+
+/// SPI transport, chip select is handled by the `SpiDevice`
+///
+/// The address byte carries the read flag in its MSB. None of the supported
+/// sensors use a separate multi-byte flag.
+pub struct SpiTransport<SPI> {
+    _bus: PhantomData<SPI>,
+}
+
+impl<SPI> SpiTransport<SPI> {
+    pub(crate) fn new() -> Self {
+        Self { _bus: PhantomData }
+    }
+}
+
+impl<SPI: SpiDevice + 'static> Transport for SpiTransport<SPI> {
+    type Bus = SPI;
+    type Error = SPI::Error;
+
+    async fn read(&self, bus: &mut SPI, reg: u8, buf: &mut [u8]) -> Result<(), SPI::Error> {
+        bus.transaction(&mut [Operation::Write(&[reg | SPI_READ]), Operation::Read(buf)])
+            .await
+    }
+
+    async fn write(&self, bus: &mut SPI, reg: u8, value: u8) -> Result<(), SPI::Error> {
+        bus.write(&[reg & !SPI_READ, value]).await
+    }
+}
@@ -0,0 +1,424 @@
+//! Register-level simulation of the IKS01A3 sensors on fake I2C and SPI buses.
+#![allow(dead_code)]
+
+use embassy_futures::block_on;
+use embassy_sync::mutex::Mutex;
+use embedded_hal::digital;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::{ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation};
+use embedded_hal_async::spi::{self, SpiDevice};
+use sensor_pack::SharedBus;
+use std::collections::VecDeque;
+
+/// Simulated sensor register map
+pub struct SimDevice {
+    pub address: u8,
+    pub regs: [u8; 256],
+    /// Whether the register pointer advances after each byte
+    auto_increment: fn(&[u8; 256]) -> bool,
+    /// Whether block data update is enabled
+    bdu: fn(&[u8; 256]) -> bool,
+    /// Whether SDO drives the data on SPI reads, false in 3-wire mode
+    spi_sdo: fn(&[u8; 256]) -> bool,
+    /// Output registers refreshed by `push_sample`
+    output: core::ops::Range<u8>,
+    pending: Option<Vec<u8>>,
+    /// FIFO output registers, reading the first one pops the next word.
+    /// Burst reads wrap from the last register back to the first.
+    fifo_out: core::ops::Range<u8>,
+    /// Write the FIFO fill level to the status registers
+    fifo_level: fn(&mut [u8; 256], usize),
+    pub fifo: VecDeque<Vec<u8>>,
+    /// Register bank an address maps to in the current state, 0 for `regs`
+    bank: fn(&[u8; 256], u8) -> usize,
+    /// Additional register banks, `banks[n - 1]` holds bank n
+    pub banks: Vec<[u8; 256]>,
+    /// Whether reading a main page register clears it, modelling latched
+    /// interrupt sources cleared on read
+    clear_on_read: fn(&[u8; 256], u8) -> bool,
+    /// Registers that silently ignore writes, modelling a faulty device
+    pub stuck: Vec<u8>,
+    /// Every register write, in order
+    pub writes: Vec<(u8, u8)>,
+}
+
+impl SimDevice {
+    /// Move the device to another I2C address
+    pub fn at(mut self, address: u8) -> Self {
+        self.address = address;
+        self
+    }
+
+    /// Queue a new sample for the output registers. Without BDU the sample
+    /// lands in the middle of the next burst read, producing a torn read;
+    /// with BDU it is applied once the transaction completes.
+    pub fn push_sample(&mut self, data: &[u8]) {
+        assert_eq!(data.len(), self.output.len());
+        self.pending = Some(data.to_vec());
+    }
+
+    /// Update the output registers immediately
+    pub fn set_output(&mut self, data: &[u8]) {
+        assert_eq!(data.len(), self.output.len());
+        let start = self.output.start as usize;
+        self.regs[start..start + data.len()].copy_from_slice(data);
+    }
+
+    /// Queue a word in the FIFO
+    pub fn push_fifo(&mut self, word: &[u8]) {
+        assert_eq!(word.len(), self.fifo_out.len());
+        self.fifo.push_back(word.to_vec());
+        (self.fifo_level)(&mut self.regs, self.fifo.len());
+    }
+
+    fn pop_fifo(&mut self) {
+        let word = self
+            .fifo
+            .pop_front()
+            .unwrap_or_else(|| vec![0; self.fifo_out.len()]);
+        let start = self.fifo_out.start as usize;
+        self.regs[start..start + word.len()].copy_from_slice(&word);
+        (self.fifo_level)(&mut self.regs, self.fifo.len());
+    }
+
+    fn apply_pending(&mut self) {
+        if let Some(data) = self.pending.take() {
+            self.set_output(&data);
+        }
+    }
+
+    fn reg_mut(&mut self, addr: u8) -> &mut u8 {
+        match (self.bank)(&self.regs, addr) {
+            0 => &mut self.regs[addr as usize],
+            n => &mut self.banks[n - 1][addr as usize],
+        }
+    }
+
+    fn read(&mut self, pointer: &mut u8, buf: &mut [u8]) {
+        for (i, byte) in buf.iter_mut().enumerate() {
+            let main = (self.bank)(&self.regs, *pointer) == 0;
+            if main && *pointer == self.fifo_out.start && !self.fifo_out.is_empty() {
+                self.pop_fifo();
+            }
+            *byte = *self.reg_mut(*pointer);
+            if main && (self.clear_on_read)(&self.regs, *pointer) {
+                self.regs[*pointer as usize] = 0;
+            }
+            if i == 0 && !(self.bdu)(&self.regs) {
+                self.apply_pending();
+            }
+            if (self.auto_increment)(&self.regs) {
+                *pointer = pointer.wrapping_add(1);
+                if *pointer == self.fifo_out.end && !self.fifo_out.is_empty() {
+                    *pointer = self.fifo_out.start;
+                }
+            }
+        }
+    }
+
+    fn write(&mut self, pointer: &mut u8, data: &[u8]) {
+        for byte in data {
+            if !self.stuck.contains(pointer) {
+                *self.reg_mut(*pointer) = *byte;
+            }
+            self.writes.push((*pointer, *byte));
+            if (self.auto_increment)(&self.regs) {
+                *pointer = pointer.wrapping_add(1);
+            }
+        }
+    }
+}
+
+/// Index in `SimDevice::banks` of the LSM6DSO embedded functions bank
+pub const EMB_FUNC_BANK: usize = 0;
+/// Index in `SimDevice::banks` of the LSM6DSO sensor hub bank
+pub const SENSOR_HUB_BANK: usize = 1;
+
+/// LSM6DSO at its default address (SA0 high)
+pub fn lsm6dso() -> SimDevice {
+    let mut regs = [0u8; 256];
+    regs[0x0F] = 0x6C; // WHO_AM_I
+    regs[0x12] = 0x04; // CTRL3_C: IF_INC
+    let mut emb_func = [0u8; 256];
+    emb_func[0x02] = 0x01; // PAGE_SEL
+    emb_func[0x5F] = 0x4B; // EMB_FUNC_ODR_CFG_B: 26 Hz FSM ODR
+    SimDevice {
+        address: 0x6B,
+        regs,
+        auto_increment: |r| r[0x12] & 0x04 != 0,
+        bdu: |r| r[0x12] & 0x40 != 0,
+        spi_sdo: |r| r[0x12] & 0x08 == 0,
+        output: 0x20..0x2E,
+        pending: None,
+        fifo_out: 0x78..0x7F,
+        // FIFO_STATUS1, FIFO_STATUS2 DIFF_FIFO[9:8]
+        fifo_level: |r, n| {
+            r[0x3A] = n as u8;
+            r[0x3B] = (r[0x3B] & !0x03) | ((n >> 8) as u8 & 0x03);
+        },
+        fifo: VecDeque::new(),
+        // FUNC_CFG_ACCESS stays mapped in every bank
+        bank: |r, addr| match (addr, r[0x01] & 0xC0) {
+            (0x01, _) => 0,
+            (_, 0x80) => 1 + EMB_FUNC_BANK,
+            (_, 0x40) => 1 + SENSOR_HUB_BANK,
+            _ => 0,
+        },
+        banks: vec![emb_func, [0u8; 256]],
+        // TAP_CFG0: LIR | INT_CLR_ON_READ clears WAKE_UP_SRC, TAP_SRC and
+        // D6D_SRC when read, otherwise they stay latched. INT_CLR_ON_READ
+        // alone stands for the embedded function status registers, whose
+        // EMB_FUNC_LIR lives in another bank.
+        clear_on_read: |r, addr| match addr {
+            0x1B..=0x1D => r[0x56] & 0x41 == 0x41,
+            0x35..=0x37 => r[0x56] & 0x40 != 0,
+            _ => false,
+        },
+        stuck: Vec::new(),
+        writes: Vec::new(),
+    }
+}
+
+/// LIS2MDL, which always auto-increments and powers up in 3-wire SPI mode
+pub fn lis2mdl() -> SimDevice {
+    let mut regs = [0u8; 256];
+    regs[0x4F] = 0x40; // WHO_AM_I
+    regs[0x60] = 0x03; // CFG_REG_A: idle
+    SimDevice {
+        address: 0x1E,
+        regs,
+        auto_increment: |_| true,
+        bdu: |r| r[0x62] & 0x10 != 0,
+        spi_sdo: |r| r[0x62] & 0x04 != 0,
+        output: 0x68..0x70,
+        pending: None,
+        fifo_out: 0..0,
+        fifo_level: |_, _| {},
+        fifo: VecDeque::new(),
+        bank: |_, _| 0,
+        banks: Vec::new(),
+        clear_on_read: |_, _| false,
+        stuck: Vec::new(),
+        writes: Vec::new(),
+    }
+}
+
+/// LPS22HH at its default address (SA0 high)
+pub fn lps22hh() -> SimDevice {
+    let mut regs = [0u8; 256];
+    regs[0x0F] = 0xB3; // WHO_AM_I
+    regs[0x11] = 0x10; // CTRL_REG2: IF_ADD_INC
+    SimDevice {
+        address: 0x5D,
+        regs,
+        auto_increment: |r| r[0x11] & 0x10 != 0,
+        bdu: |r| r[0x10] & 0x02 != 0,
+        spi_sdo: |r| r[0x10] & 0x01 == 0,
+        output: 0x28..0x2D,
+        pending: None,
+        fifo_out: 0x78..0x7D,
+        // FIFO_STATUS1
+        fifo_level: |r, n| r[0x25] = n as u8,
+        fifo: VecDeque::new(),
+        bank: |_, _| 0,
+        banks: Vec::new(),
+        clear_on_read: |_, _| false,
+        stuck: Vec::new(),
+        writes: Vec::new(),
+    }
+}
+
+/// Fake I2C bus with any number of simulated devices attached
+pub struct SimBus {
+    pub devices: Vec<SimDevice>,
+}
+
+impl SimBus {
+    pub fn device(&mut self, address: u8) -> &mut SimDevice {
+        self.devices
+            .iter_mut()
+            .find(|d| d.address == address)
+            .expect("no simulated device at this address")
+    }
+}
+
+impl ErrorType for SimBus {
+    type Error = ErrorKind;
+}
+
+impl I2c for SimBus {
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let device = self
+            .devices
+            .iter_mut()
+            .find(|d| d.address == address)
+            .ok_or(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address))?;
+
+        let mut pointer = 0u8;
+        for op in operations.iter_mut() {
+            match op {
+                Operation::Write(data) => {
+                    if let Some((reg, data)) = data.split_first() {
+                        pointer = *reg;
+                        device.write(&mut pointer, data);
+                    }
+                }
+                Operation::Read(buf) => device.read(&mut pointer, buf),
+            }
+        }
+        device.apply_pending();
+        Ok(())
+    }
+}
+
+/// Fake SPI device, the chip select of a single simulated sensor
+pub struct SimSpi {
+    pub device: SimDevice,
+}
+
+impl spi::ErrorType for SimSpi {
+    type Error = spi::ErrorKind;
+}
+
+impl SpiDevice for SimSpi {
+    async fn transaction(
+        &mut self,
+        operations: &mut [spi::Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        let device = &mut self.device;
+        // The first byte carries the read flag and the register address
+        let mut command = None;
+        let mut pointer = 0u8;
+        for op in operations.iter_mut() {
+            match op {
+                spi::Operation::Write(data) => {
+                    let data = match command {
+                        Some(_) => &data[..],
+                        None => {
+                            let (first, rest) = data.split_first().ok_or(spi::ErrorKind::Other)?;
+                            command = Some(*first);
+                            pointer = first & 0x7F;
+                            rest
+                        }
+                    };
+                    if command.is_some_and(|c| c & 0x80 == 0) {
+                        device.write(&mut pointer, data);
+                    }
+                }
+                spi::Operation::Read(buf) if command.is_some_and(|c| c & 0x80 != 0) => {
+                    if (device.spi_sdo)(&device.regs) {
+                        device.read(&mut pointer, buf);
+                    } else {
+                        // Nothing drives MISO in 3-wire mode
+                        buf.fill(0xFF);
+                    }
+                }
+                _ => return Err(spi::ErrorKind::Other),
+            }
+        }
+        device.apply_pending();
+        Ok(())
+    }
+}
+
+/// Fake data-ready pin whose waits complete immediately
+#[derive(Default)]
+pub struct SimPin {
+    /// Number of completed waits
+    pub waits: usize,
+    /// Fail every wait, modelling a broken GPIO
+    pub broken: bool,
+    /// Waits that complete before the pin stays low, after which waits fail
+    /// instead of hanging; `None` for a pin that is always asserted
+    pub assertions: Option<usize>,
+}
+
+impl SimPin {
+    fn wait(&mut self) -> Result<(), digital::ErrorKind> {
+        if self.broken || self.assertions.is_some_and(|n| self.waits >= n) {
+            return Err(digital::ErrorKind::Other);
+        }
+        self.waits += 1;
+        Ok(())
+    }
+}
+
+impl digital::ErrorType for SimPin {
+    type Error = digital::ErrorKind;
+}
+
+impl Wait for SimPin {
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        self.wait()
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        self.wait()
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait()
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait()
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait()
+    }
+}
+
+/// Leak a shared bus holding the given devices so it can be handed to the drivers
+pub fn bus(devices: Vec<SimDevice>) -> &'static SharedBus<SimBus> {
+    Box::leak(Box::new(Mutex::new(Some(SimBus { devices }))))
+}
+
+/// Leak a shared bus that has not been filled in by the application yet
+pub fn uninitialized_bus() -> &'static SharedBus<SimBus> {
+    Box::leak(Box::new(Mutex::new(None)))
+}
+
+/// Run `f` on the simulated device at `address`
+pub fn with_device<R>(
+    bus: &'static SharedBus<SimBus>,
+    address: u8,
+    f: impl FnOnce(&mut SimDevice) -> R,
+) -> R {
+    let mut guard = block_on(bus.lock());
+    f(guard.as_mut().unwrap().device(address))
+}
+
+/// Current value of a simulated register
+pub fn reg(bus: &'static SharedBus<SimBus>, address: u8, reg: u8) -> u8 {
+    with_device(bus, address, |d| d.regs[reg as usize])
+}
+
+/// Current value of a register in one of the additional banks of the
+/// simulated device at `address`
+pub fn bank_reg(bus: &'static SharedBus<SimBus>, address: u8, bank: usize, reg: u8) -> u8 {
+    with_device(bus, address, |d| d.banks[bank][reg as usize])
+}
+
+/// Leak a shared SPI bus with a single device behind its chip select
+pub fn spi_bus(device: SimDevice) -> &'static SharedBus<SimSpi> {
+    Box::leak(Box::new(Mutex::new(Some(SimSpi { device }))))
+}
+
+/// Run `f` on the simulated device behind an SPI bus
+pub fn with_spi_device<R>(
+    bus: &'static SharedBus<SimSpi>,
+    f: impl FnOnce(&mut SimDevice) -> R,
+) -> R {
+    let mut guard = block_on(bus.lock());
+    f(&mut guard.as_mut().unwrap().device)
+}
+
+/// Current value of a simulated register behind an SPI bus
+pub fn spi_reg(bus: &'static SharedBus<SimSpi>, reg: u8) -> u8 {
+    with_spi_device(bus, |d| d.regs[reg as usize])
+}
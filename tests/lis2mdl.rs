@@ -0,0 +1,154 @@
+mod common;
+
+use common::{bus, lis2mdl, reg, spi_bus, spi_reg, with_device, SimPin};
+use embassy_futures::block_on;
+use sensor_pack::lis2mdl::{Error, Lis2mdl, Lis2mdlConfig, ModeOp, Odr, RawSample, Register};
+
+const ADDR: u8 = 0x1E;
+
+#[test]
+fn check_device_id() {
+    let bus = bus(vec![lis2mdl()]);
+    let sensor = Lis2mdl::new(bus);
+
+    assert_eq!(block_on(sensor.check_device_id()), Ok(()));
+}
+
+#[test]
+fn apply_config() {
+    let bus = bus(vec![lis2mdl()]);
+    let sensor = Lis2mdl::new(bus);
+
+    assert_eq!(
+        block_on(sensor.apply_config(&Lis2mdlConfig::default())),
+        Ok(())
+    );
+    // Temperature compensation, 100 Hz, continuous mode
+    assert_eq!(reg(bus, ADDR, 0x60), 0x8C);
+    assert_eq!(reg(bus, ADDR, 0x61), 0x00);
+    // BDU
+    assert_eq!(reg(bus, ADDR, 0x62), 0x10);
+}
+
+#[test]
+fn apply_custom_config() {
+    let bus = bus(vec![lis2mdl()]);
+    let sensor = Lis2mdl::new(bus);
+    let config = Lis2mdlConfig::default()
+        .odr(Odr::Hz10)
+        .mode(ModeOp::Single)
+        .temperature_compensation(false)
+        .low_power(true)
+        .low_pass_filter(true)
+        .offset_cancellation(true)
+        .block_data_update(false);
+
+    assert_eq!(block_on(sensor.apply_config(&config)), Ok(()));
+    assert_eq!(reg(bus, ADDR, 0x60), 0x11);
+    assert_eq!(reg(bus, ADDR, 0x61), 0x03);
+    assert_eq!(reg(bus, ADDR, 0x62), 0x00);
+}
+
+#[test]
+fn apply_config_mismatch() {
+    let bus = bus(vec![lis2mdl()]);
+    let sensor = Lis2mdl::new(bus);
+    with_device(bus, ADDR, |d| d.stuck.push(0x60));
+
+    assert_eq!(
+        block_on(sensor.apply_config(&Lis2mdlConfig::default())),
+        Err(Error::ConfigMismatch {
+            register: Register::CFG_REG_A,
+            written: 0x8C,
+            read: 0x03,
+        })
+    );
+}
+
+#[test]
+fn sample() {
+    let bus = bus(vec![lis2mdl()]);
+    let sensor = Lis2mdl::new(bus);
+    // X = 1000, Y = -1000, Z = 0, T = 80
+    with_device(bus, ADDR, |d| {
+        d.set_output(&[0xE8, 0x03, 0x18, 0xFC, 0x00, 0x00, 0x50, 0x00]);
+    });
+
+    assert_eq!(block_on(sensor.sample()), Err(Error::DataNotReady));
+
+    // STATUS_REG: ZYXDA
+    with_device(bus, ADDR, |d| d.regs[0x67] = 0x08);
+    assert_eq!(
+        block_on(sensor.sample_raw()),
+        Ok(RawSample {
+            magnetic_field: [1000, -1000, 0],
+            temperature: 80,
+        })
+    );
+
+    let sample = block_on(sensor.sample()).unwrap();
+    assert_eq!(sample.magnetic_field.x, 1.5);
+    assert_eq!(sample.magnetic_field.y, -1.5);
+    assert_eq!(sample.magnetic_field.z, 0.0);
+    assert_eq!(sample.magnetic_field.microtesla(), [150.0, -150.0, 0.0]);
+    assert_eq!(sample.temperature.0, 35.0);
+}
+
+#[test]
+fn set_odr_keeps_other_fields() {
+    let bus = bus(vec![lis2mdl()]);
+    let sensor = Lis2mdl::new(bus);
+    block_on(sensor.apply_config(&Lis2mdlConfig::default().low_pass_filter(true))).unwrap();
+    // DRDY_ON_PIN, set outside of the configuration
+    with_device(bus, ADDR, |d| d.regs[0x62] |= 0x01);
+
+    assert_eq!(block_on(sensor.set_odr(Odr::Hz20)), Ok(()));
+    assert_eq!(reg(bus, ADDR, 0x60), 0x84);
+
+    assert_eq!(block_on(sensor.set_mode(ModeOp::Idle)), Ok(()));
+    assert_eq!(reg(bus, ADDR, 0x60), 0x87);
+
+    block_on(sensor.apply_config(&Lis2mdlConfig::default().low_pass_filter(true))).unwrap();
+    assert_eq!(reg(bus, ADDR, 0x61), 0x01);
+    assert_eq!(reg(bus, ADDR, 0x62), 0x11);
+}
+
+#[test]
+fn spi_4wire() {
+    let bus = spi_bus(lis2mdl());
+    let sensor = Lis2mdl::new_spi(bus);
+
+    // 3-wire at power-up, nothing answers on MISO
+    assert_eq!(
+        block_on(sensor.check_device_id()),
+        Err(Error::WrongDeviceId(0xFF))
+    );
+
+    assert_eq!(block_on(sensor.enable_4wire_spi()), Ok(()));
+    assert_eq!(block_on(sensor.check_device_id()), Ok(()));
+    assert_eq!(block_on(sensor.disable_i2c()), Ok(()));
+    assert_eq!(
+        block_on(sensor.apply_config(&Lis2mdlConfig::default())),
+        Ok(())
+    );
+    // I2C_DIS | BDU | 4WSPI
+    assert_eq!(spi_reg(bus, 0x62), 0x34);
+}
+
+#[test]
+fn data_ready_interrupt() {
+    let bus = bus(vec![lis2mdl()]);
+    let sensor = Lis2mdl::new(bus);
+    block_on(sensor.apply_config(&Lis2mdlConfig::default())).unwrap();
+
+    assert_eq!(block_on(sensor.route_data_ready(true)), Ok(()));
+    // BDU | DRDY_ON_PIN
+    assert_eq!(reg(bus, ADDR, 0x62), 0x11);
+
+    let mut pin = SimPin::default();
+    assert_eq!(block_on(sensor.wait_for_data(&mut pin)), Ok(()));
+    assert_eq!(pin.waits, 1);
+
+    assert_eq!(block_on(sensor.route_data_ready(false)), Ok(()));
+    assert_eq!(reg(bus, ADDR, 0x62), 0x10);
+}
@@ -0,0 +1,204 @@
+mod common;
+
+use common::{bus, lps22hh, reg, spi_bus, spi_reg, with_device, with_spi_device, SimPin};
+use embassy_futures::block_on;
+use sensor_pack::lps22hh::{Error, Lps22hh, Lps22hhConfig, Odr, RawSample, Register, LPF};
+use sensor_pack::SlaveAddr;
+
+const ADDR: u8 = 0x5D;
+
+#[test]
+fn check_device_id() {
+    let bus = bus(vec![lps22hh()]);
+    let sensor = Lps22hh::new(bus, SlaveAddr::Default);
+
+    assert_eq!(block_on(sensor.check_device_id()), Ok(()));
+}
+
+#[test]
+fn apply_config() {
+    let bus = bus(vec![lps22hh()]);
+    let sensor = Lps22hh::new(bus, SlaveAddr::Default);
+
+    assert_eq!(
+        block_on(sensor.apply_config(&Lps22hhConfig::default())),
+        Ok(())
+    );
+    // 50 Hz, LPF at ODR/9, BDU
+    assert_eq!(reg(bus, ADDR, 0x10), 0x4A);
+    // Auto-increment, low-noise
+    assert_eq!(reg(bus, ADDR, 0x11), 0x12);
+}
+
+#[test]
+fn apply_custom_config() {
+    let bus = bus(vec![lps22hh()]);
+    let sensor = Lps22hh::new(bus, SlaveAddr::Default);
+    let config = Lps22hhConfig::default()
+        .odr(Odr::Hz200)
+        .lpf(LPF::DIV_20)
+        .low_noise(false)
+        .block_data_update(false);
+
+    assert_eq!(block_on(sensor.apply_config(&config)), Ok(()));
+    assert_eq!(reg(bus, ADDR, 0x10), 0x7C);
+    assert_eq!(reg(bus, ADDR, 0x11), 0x10);
+}
+
+#[test]
+fn low_noise_off_at_200hz() {
+    let bus = bus(vec![lps22hh()]);
+    let sensor = Lps22hh::new(bus, SlaveAddr::Default);
+
+    // Low-noise mode is the default but not allowed at 200 Hz
+    let config = Lps22hhConfig::default().odr(Odr::Hz200);
+    assert_eq!(config.ctrl_reg2(), 0x10);
+    assert_eq!(block_on(sensor.apply_config(&config)), Ok(()));
+    assert_eq!(reg(bus, ADDR, 0x11), 0x10);
+    assert_eq!(Lps22hhConfig::default().odr(Odr::Hz100).ctrl_reg2(), 0x12);
+}
+
+#[test]
+fn apply_config_mismatch() {
+    let bus = bus(vec![lps22hh()]);
+    let sensor = Lps22hh::new(bus, SlaveAddr::Default);
+    with_device(bus, ADDR, |d| d.stuck.push(0x11));
+
+    assert_eq!(
+        block_on(sensor.apply_config(&Lps22hhConfig::default())),
+        Err(Error::ConfigMismatch {
+            register: Register::CTRL_REG2,
+            written: 0x12,
+            read: 0x10,
+        })
+    );
+}
+
+#[test]
+fn sample() {
+    let bus = bus(vec![lps22hh()]);
+    let sensor = Lps22hh::new(bus, SlaveAddr::Default);
+    // P = 1013.25 hPa * 4096, T = 23.45 C
+    with_device(bus, ADDR, |d| d.set_output(&[0x00, 0x54, 0x3F, 0x29, 0x09]));
+
+    assert_eq!(block_on(sensor.sample()), Err(Error::DataNotReady));
+
+    // STATUS: P_DA | T_DA
+    with_device(bus, ADDR, |d| d.regs[0x27] = 0x03);
+    assert_eq!(
+        block_on(sensor.sample_raw()),
+        Ok(RawSample {
+            pressure: 4_150_272,
+            temperature: 2345,
+        })
+    );
+
+    let sample = block_on(sensor.sample()).unwrap();
+    assert_eq!(sample.pressure.0, 101_325.0);
+    assert_eq!(sample.pressure.hectopascal(), 1013.25);
+    assert!((sample.temperature.0 - 23.45).abs() < 1e-4);
+}
+
+#[test]
+fn sample_negative_pressure_counts() {
+    let bus = bus(vec![lps22hh()]);
+    let sensor = Lps22hh::new(bus, SlaveAddr::Default);
+    with_device(bus, ADDR, |d| {
+        d.regs[0x27] = 0x03;
+        d.set_output(&[0xFF, 0xFF, 0xFF, 0x00, 0x00]);
+    });
+
+    assert_eq!(block_on(sensor.sample_raw()).unwrap().pressure, -1);
+}
+
+#[test]
+fn set_odr_keeps_other_fields() {
+    let bus = bus(vec![lps22hh()]);
+    let sensor = Lps22hh::new(bus, SlaveAddr::Default);
+    block_on(sensor.apply_config(&Lps22hhConfig::default())).unwrap();
+
+    assert_eq!(block_on(sensor.set_odr(Odr::Hz10)), Ok(()));
+    // BDU and LPF untouched
+    assert_eq!(reg(bus, ADDR, 0x10), 0x2A);
+
+    assert_eq!(block_on(sensor.set_lpf(LPF::DIV_2)), Ok(()));
+    assert_eq!(reg(bus, ADDR, 0x10), 0x22);
+}
+
+#[test]
+fn set_odr_200hz_leaves_low_noise() {
+    let bus = bus(vec![lps22hh()]);
+    let sensor = Lps22hh::new(bus, SlaveAddr::Default);
+    block_on(sensor.apply_config(&Lps22hhConfig::default())).unwrap();
+    // IF_ADD_INC | LOW_NOISE_EN
+    assert_eq!(reg(bus, ADDR, 0x11), 0x12);
+
+    assert_eq!(block_on(sensor.set_odr(Odr::Hz200)), Ok(()));
+    assert_eq!(reg(bus, ADDR, 0x10) & 0x70, 0x70);
+    assert_eq!(reg(bus, ADDR, 0x11), 0x10);
+}
+
+#[test]
+fn apply_config_keeps_interrupt_settings() {
+    let bus = bus(vec![lps22hh()]);
+    let sensor = Lps22hh::new(bus, SlaveAddr::Default);
+    // CTRL_REG2: INT_H_L | PP_OD
+    with_device(bus, ADDR, |d| d.regs[0x11] |= 0x60);
+
+    block_on(sensor.apply_config(&Lps22hhConfig::default())).unwrap();
+    assert_eq!(reg(bus, ADDR, 0x11), 0x72);
+}
+
+#[test]
+fn redundant_pair_on_one_bus() {
+    let bus = bus(vec![lps22hh(), lps22hh().at(0x5C)]);
+    let primary = Lps22hh::new(bus, SlaveAddr::Default);
+    let backup = Lps22hh::new(bus, SlaveAddr::Alternate);
+
+    assert_eq!(block_on(primary.check_device_id()), Ok(()));
+    assert_eq!(block_on(backup.check_device_id()), Ok(()));
+
+    block_on(primary.apply_config(&Lps22hhConfig::default())).unwrap();
+    block_on(backup.apply_config(&Lps22hhConfig::default().odr(Odr::Hz10))).unwrap();
+    assert_eq!(reg(bus, 0x5D, 0x10), 0x4A);
+    assert_eq!(reg(bus, 0x5C, 0x10), 0x2A);
+}
+
+#[test]
+fn spi_sample() {
+    let bus = spi_bus(lps22hh());
+    let sensor = Lps22hh::new_spi(bus);
+
+    assert_eq!(block_on(sensor.check_device_id()), Ok(()));
+    assert_eq!(block_on(sensor.disable_i2c()), Ok(()));
+    assert_eq!(spi_reg(bus, 0x0E), 0x01);
+    assert_eq!(
+        block_on(sensor.apply_config(&Lps22hhConfig::default())),
+        Ok(())
+    );
+
+    with_spi_device(bus, |d| {
+        d.regs[0x27] = 0x03;
+        d.set_output(&[0x00, 0x54, 0x3F, 0x29, 0x09]);
+    });
+    assert_eq!(
+        block_on(sensor.sample_raw()),
+        Ok(RawSample {
+            pressure: 4_150_272,
+            temperature: 2345,
+        })
+    );
+}
+
+#[test]
+fn data_ready_interrupt() {
+    let bus = bus(vec![lps22hh()]);
+    let sensor = Lps22hh::new(bus, SlaveAddr::Default);
+
+    assert_eq!(block_on(sensor.route_data_ready(true)), Ok(()));
+    assert_eq!(reg(bus, ADDR, 0x12), 0x04);
+
+    let mut pin = SimPin::default();
+    assert_eq!(block_on(sensor.wait_for_data(&mut pin)), Ok(()));
+    assert_eq!(pin.waits, 1);
+}
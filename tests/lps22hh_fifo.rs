@@ -0,0 +1,88 @@
+mod common;
+
+use common::{bus, lps22hh, reg, with_device};
+use embassy_futures::block_on;
+use sensor_pack::lps22hh::{FifoConfig, FifoMode, FifoStatus, Lps22hh, Sample};
+use sensor_pack::SlaveAddr;
+
+const ADDR: u8 = 0x5D;
+
+#[test]
+fn configure_fifo() {
+    let bus = bus(vec![lps22hh()]);
+    let sensor = Lps22hh::new(bus, SlaveAddr::Default);
+
+    assert_eq!(
+        block_on(sensor.configure_fifo(&FifoConfig::default())),
+        Ok(())
+    );
+    assert_eq!(reg(bus, ADDR, 0x13), 0x02);
+    assert_eq!(reg(bus, ADDR, 0x14), 0x00);
+
+    let config = FifoConfig::default()
+        .mode(FifoMode::BypToFifo)
+        .watermark(200)
+        .stop_on_watermark(true);
+    assert_eq!(block_on(sensor.configure_fifo(&config)), Ok(()));
+    assert_eq!(reg(bus, ADDR, 0x13), 0x0D);
+    // Saturated at 7 bits
+    assert_eq!(reg(bus, ADDR, 0x14), 0x7F);
+
+    assert_eq!(block_on(sensor.set_fifo_mode(FifoMode::Bypass)), Ok(()));
+    assert_eq!(reg(bus, ADDR, 0x13), 0x08);
+
+    assert_eq!(block_on(sensor.route_fifo_watermark(true)), Ok(()));
+    assert_eq!(reg(bus, ADDR, 0x12), 0x10);
+}
+
+#[test]
+fn read_fifo() {
+    let bus = bus(vec![lps22hh()]);
+    let sensor = Lps22hh::new(bus, SlaveAddr::Default);
+    // P = 1013.25 hPa * 4096, T = 23.45 C, then P = -1 LSB, T = -1 LSB
+    with_device(bus, ADDR, |d| {
+        for _ in 0..3 {
+            d.push_fifo(&[0x00, 0x54, 0x3F, 0x29, 0x09]);
+        }
+        d.push_fifo(&[0xFF, 0xFF, 0xFF, 0xFF, 0xFF]);
+        // FIFO_STATUS2: FIFO_OVR_IA
+        d.regs[0x26] = 0x40;
+    });
+
+    assert_eq!(
+        block_on(sensor.fifo_status()),
+        Ok(FifoStatus {
+            level: 4,
+            watermark: false,
+            overrun: true,
+            full: false,
+        })
+    );
+
+    let mut samples = [Sample::default(); 8];
+    assert_eq!(block_on(sensor.read_fifo(&mut samples)), Ok(4));
+    for sample in &samples[..3] {
+        assert_eq!(sample.pressure.hectopascal(), 1013.25);
+        assert!((sample.temperature.0 - 23.45).abs() < 1e-4);
+    }
+    assert_eq!(samples[3].pressure.0, -100.0 / 4096.0);
+    assert_eq!(samples[3].temperature.0, -0.01);
+    assert_eq!(block_on(sensor.fifo_status()).unwrap().level, 0);
+}
+
+#[test]
+fn read_full_fifo() {
+    let bus = bus(vec![lps22hh()]);
+    let sensor = Lps22hh::new(bus, SlaveAddr::Default);
+    with_device(bus, ADDR, |d| {
+        for i in 0..128u8 {
+            d.push_fifo(&[i, 0x00, 0x00, i, 0x00]);
+        }
+    });
+
+    let mut samples = [Sample::default(); 128];
+    assert_eq!(block_on(sensor.read_fifo(&mut samples)), Ok(128));
+    for (i, sample) in samples.iter().enumerate() {
+        assert_eq!(sample.temperature.0, i as f32 * 0.01);
+    }
+}
@@ -0,0 +1,224 @@
+mod common;
+
+use common::{
+    bus, lsm6dso, reg, spi_bus, spi_reg, uninitialized_bus, with_device, with_spi_device, SimPin,
+};
+use embassy_futures::block_on;
+use embedded_hal_async::i2c::{ErrorKind, NoAcknowledgeSource};
+use sensor_pack::lsm6dso::{
+    AccOdr, AccScale, Error, GyrOdr, GyrScale, InterruptPin, Lsm6dso, Lsm6dsoConfig, RawSample,
+    Rounding,
+};
+use sensor_pack::SlaveAddr;
+
+const ADDR: u8 = 0x6B;
+
+#[test]
+fn check_device_id() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+
+    assert_eq!(block_on(sensor.check_device_id()), Ok(()));
+
+    with_device(bus, ADDR, |d| d.regs[0x0F] = 0x6A);
+    assert_eq!(
+        block_on(sensor.check_device_id()),
+        Err(Error::WrongDeviceId(0x6A))
+    );
+}
+
+#[test]
+fn check_device_id_without_device() {
+    let bus = bus(vec![]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+
+    assert_eq!(
+        block_on(sensor.check_device_id()),
+        Err(Error::Bus(ErrorKind::NoAcknowledge(
+            NoAcknowledgeSource::Address
+        )))
+    );
+}
+
+#[test]
+fn bus_not_initialized() {
+    let sensor = Lsm6dso::new(uninitialized_bus(), SlaveAddr::Default);
+
+    assert_eq!(
+        block_on(sensor.check_device_id()),
+        Err(Error::BusNotInitialized)
+    );
+}
+
+#[test]
+fn apply_config() {
+    let bus = bus(vec![lsm6dso()]);
+    let mut sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+
+    assert_eq!(
+        block_on(sensor.apply_config(&Lsm6dsoConfig::default())),
+        Ok(())
+    );
+    // BDU, IF_INC
+    assert_eq!(reg(bus, ADDR, 0x12), 0x44);
+    // 104 Hz, 4 g
+    assert_eq!(reg(bus, ADDR, 0x10), 0x48);
+    // 104 Hz, 2000 dps
+    assert_eq!(reg(bus, ADDR, 0x11), 0x4C);
+}
+
+#[test]
+fn apply_custom_config() {
+    let bus = bus(vec![lsm6dso()]);
+    let mut sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    let config = Lsm6dsoConfig::default()
+        .acc_odr(AccOdr::KHz1_66)
+        .acc_scale(AccScale::G16)
+        .gyr_odr(GyrOdr::PowerDown)
+        .gyr_scale(GyrScale::Dps250)
+        .rounding(Rounding::Both);
+
+    assert_eq!(block_on(sensor.apply_config(&config)), Ok(()));
+    assert_eq!(reg(bus, ADDR, 0x10), 0x84);
+    assert_eq!(reg(bus, ADDR, 0x11), 0x00);
+    assert_eq!(reg(bus, ADDR, 0x14), 0x60);
+}
+
+#[test]
+fn init_gyroscope_125_dps() {
+    let bus = bus(vec![lsm6dso()]);
+    let mut sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+
+    assert_eq!(
+        block_on(sensor.init_gyroscope(GyrOdr::KHz6_66, GyrScale::Dps125)),
+        Ok(())
+    );
+    assert_eq!(reg(bus, ADDR, 0x11), 0xA2);
+}
+
+#[test]
+fn sample() {
+    let bus = bus(vec![lsm6dso()]);
+    let mut sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    block_on(sensor.init_accelerometer(AccOdr::Hz104, AccScale::G4)).unwrap();
+    block_on(sensor.init_gyroscope(GyrOdr::Hz104, GyrScale::Dps500)).unwrap();
+
+    // T = 512, G = (1000, -1000, 0), A = (8197, 0, -8197)
+    with_device(bus, ADDR, |d| {
+        d.set_output(&[
+            0x00, 0x02, 0xE8, 0x03, 0x18, 0xFC, 0x00, 0x00, 0x05, 0x20, 0x00, 0x00, 0xFB, 0xDF,
+        ])
+    });
+    assert_eq!(block_on(sensor.sample()), Err(Error::DataNotReady));
+
+    // STATUS_REG: TDA | GDA | XLDA
+    with_device(bus, ADDR, |d| d.regs[0x1E] = 0x07);
+    assert_eq!(
+        block_on(sensor.sample_raw()),
+        Ok(RawSample {
+            temperature: 512,
+            angular_rate: [1000, -1000, 0],
+            acceleration: [8197, 0, -8197],
+        })
+    );
+
+    let sample = block_on(sensor.sample()).unwrap();
+    assert_eq!(sample.temperature.0, 27.0);
+    assert_eq!(sample.angular_rate.x, 17.5);
+    assert_eq!(sample.angular_rate.y, -17.5);
+    assert!((sample.acceleration.x - 1.0).abs() < 1e-3);
+    assert!((sample.acceleration.z + 1.0).abs() < 1e-3);
+}
+
+#[test]
+fn init_accelerometer_keeps_lpf2() {
+    let bus = bus(vec![lsm6dso()]);
+    let mut sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    // CTRL1_XL: LPF2_XL_EN, CTRL3_C: H_LACTIVE
+    with_device(bus, ADDR, |d| {
+        d.regs[0x10] = 0x02;
+        d.regs[0x12] |= 0x20;
+    });
+
+    block_on(sensor.apply_config(&Lsm6dsoConfig::default())).unwrap();
+    assert_eq!(reg(bus, ADDR, 0x10), 0x4A);
+    assert_eq!(reg(bus, ADDR, 0x12), 0x64);
+
+    block_on(sensor.init_accelerometer(AccOdr::Hz833, AccScale::G2)).unwrap();
+    assert_eq!(reg(bus, ADDR, 0x10), 0x72);
+}
+
+#[test]
+fn raw_address() {
+    let bus = bus(vec![lsm6dso().at(0x6A)]);
+
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Raw(0x6A));
+    assert_eq!(block_on(sensor.check_device_id()), Ok(()));
+
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    assert!(block_on(sensor.check_device_id()).is_err());
+}
+
+#[test]
+fn spi_sample() {
+    let bus = spi_bus(lsm6dso());
+    let mut sensor = Lsm6dso::new_spi(bus);
+
+    assert_eq!(block_on(sensor.check_device_id()), Ok(()));
+    assert_eq!(block_on(sensor.disable_i2c()), Ok(()));
+    assert_eq!(spi_reg(bus, 0x13), 0x04);
+    assert_eq!(
+        block_on(sensor.apply_config(&Lsm6dsoConfig::default())),
+        Ok(())
+    );
+    assert_eq!(spi_reg(bus, 0x12), 0x44);
+
+    with_spi_device(bus, |d| {
+        d.regs[0x1E] = 0x07;
+        d.set_output(&[
+            0x00, 0x02, 0xE8, 0x03, 0x18, 0xFC, 0x00, 0x00, 0x05, 0x20, 0x00, 0x00, 0xFB, 0xDF,
+        ]);
+    });
+    assert_eq!(
+        block_on(sensor.sample_raw()),
+        Ok(RawSample {
+            temperature: 512,
+            angular_rate: [1000, -1000, 0],
+            acceleration: [8197, 0, -8197],
+        })
+    );
+}
+
+#[test]
+fn data_ready_interrupt() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    // INT1_CTRL: INT1_FIFO_TH, set outside of the data-ready routing
+    with_device(bus, ADDR, |d| d.regs[0x0D] = 0x08);
+
+    assert_eq!(
+        block_on(sensor.route_data_ready(InterruptPin::Int1, true)),
+        Ok(())
+    );
+    assert_eq!(reg(bus, ADDR, 0x0D), 0x0B);
+    assert_eq!(
+        block_on(sensor.route_data_ready(InterruptPin::Int2, true)),
+        Ok(())
+    );
+    assert_eq!(reg(bus, ADDR, 0x0E), 0x03);
+    assert_eq!(
+        block_on(sensor.route_data_ready(InterruptPin::Int1, false)),
+        Ok(())
+    );
+    assert_eq!(reg(bus, ADDR, 0x0D), 0x08);
+
+    let mut pin = SimPin::default();
+    assert_eq!(block_on(sensor.wait_for_data(&mut pin)), Ok(()));
+    assert_eq!(pin.waits, 1);
+
+    pin.broken = true;
+    assert_eq!(
+        block_on(sensor.wait_for_data(&mut pin)),
+        Err(Error::Pin(embedded_hal::digital::ErrorKind::Other))
+    );
+}
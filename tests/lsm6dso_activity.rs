@@ -0,0 +1,135 @@
+mod common;
+
+use common::{bus, lsm6dso, reg, with_device, SimPin};
+use embassy_futures::block_on;
+use embedded_hal::digital::ErrorKind;
+use sensor_pack::lsm6dso::{
+    AccOdr, AccScale, Axis, Error, FreeFallConfig, FreeFallThreshold, InactivityMode, InterruptPin,
+    Lsm6dso, MotionEvent, WakeUpConfig,
+};
+use sensor_pack::SlaveAddr;
+
+const ADDR: u8 = 0x6B;
+
+#[test]
+fn configure_free_fall() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    // WAKE_UP_DUR: WAKE_DUR = 1, set outside of the configuration
+    with_device(bus, ADDR, |d| d.regs[0x5C] = 0x20);
+
+    assert_eq!(
+        block_on(sensor.configure_free_fall(&FreeFallConfig::default())),
+        Ok(())
+    );
+    // FF_DUR = 6, 312 mg
+    assert_eq!(reg(bus, ADDR, 0x5D), 0x33);
+    assert_eq!(reg(bus, ADDR, 0x5C), 0x20);
+    // INTERRUPTS_ENABLE
+    assert_eq!(reg(bus, ADDR, 0x58), 0x80);
+
+    let config = FreeFallConfig::default()
+        .threshold(FreeFallThreshold::Mg500)
+        .duration(100);
+    assert_eq!(block_on(sensor.configure_free_fall(&config)), Ok(()));
+    // Saturated at 63, FF_DUR5 in WAKE_UP_DUR
+    assert_eq!(reg(bus, ADDR, 0x5D), 0xFF);
+    assert_eq!(reg(bus, ADDR, 0x5C), 0xA0);
+}
+
+#[test]
+fn configure_wake_up() {
+    let bus = bus(vec![lsm6dso()]);
+    let mut sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    block_on(sensor.init_accelerometer(AccOdr::Hz104, AccScale::G2)).unwrap();
+    // WAKE_UP_THS: SINGLE_DOUBLE_TAP, WAKE_UP_DUR: FF_DUR5
+    with_device(bus, ADDR, |d| {
+        d.regs[0x5B] = 0x80;
+        d.regs[0x5C] = 0x80;
+    });
+
+    assert_eq!(
+        block_on(sensor.configure_wake_up(&WakeUpConfig::default())),
+        Ok(())
+    );
+    // 0.1 g at 2 g full scale with the fine weight
+    assert_eq!(reg(bus, ADDR, 0x5B), 0x8D);
+    assert_eq!(reg(bus, ADDR, 0x5C), 0x90);
+    assert_eq!(reg(bus, ADDR, 0x58), 0x80);
+
+    let config = WakeUpConfig::default()
+        .threshold(1.0)
+        .duration(2)
+        .sleep_duration(3)
+        .inactivity(InactivityMode::GyrSleep);
+    assert_eq!(block_on(sensor.configure_wake_up(&config)), Ok(()));
+    // Coarse weight above a quarter of the full scale
+    assert_eq!(reg(bus, ADDR, 0x5B), 0xA0);
+    assert_eq!(reg(bus, ADDR, 0x5C), 0xC3);
+    assert_eq!(reg(bus, ADDR, 0x58), 0xC0);
+
+    // Saturated at 6 bits
+    assert_eq!(
+        WakeUpConfig::default()
+            .threshold(3.0)
+            .wake_up_ths(AccScale::G2),
+        0x3F
+    );
+}
+
+#[test]
+fn motion_events() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    // TAP_CFG0: TAP_Z_EN, set outside of the latch setting
+    with_device(bus, ADDR, |d| d.regs[0x56] = 0x02);
+
+    assert_eq!(block_on(sensor.latch_interrupts(true)), Ok(()));
+    // INT_CLR_ON_READ | LIR
+    assert_eq!(reg(bus, ADDR, 0x56), 0x43);
+    assert_eq!(
+        block_on(sensor.route_motion(InterruptPin::Int1, true, true, false)),
+        Ok(())
+    );
+    assert_eq!(reg(bus, ADDR, 0x5E), 0x30);
+    assert_eq!(
+        block_on(sensor.route_motion(InterruptPin::Int2, false, false, true)),
+        Ok(())
+    );
+    assert_eq!(reg(bus, ADDR, 0x5F), 0x80);
+
+    assert_eq!(block_on(sensor.read_motion_event()), Ok(None));
+
+    // WAKE_UP_SRC: SLEEP_CHANGE_IA | WU_IA | Y_WU. The pin is still high on
+    // the second wait, as if the latched line had not dropped yet.
+    with_device(bus, ADDR, |d| d.regs[0x1B] = 0x4A);
+    let mut pin = SimPin {
+        assertions: Some(2),
+        ..SimPin::default()
+    };
+    let mut events = sensor.motion_events(&mut pin);
+    assert_eq!(
+        block_on(events.next()),
+        Ok(MotionEvent {
+            free_fall: false,
+            wake_up: Some(Axis::Y),
+            sleep_change: true,
+            sleeping: false,
+        })
+    );
+    // Reading WAKE_UP_SRC cleared the event, so it is reported once
+    assert_eq!(block_on(events.next()), Err(Error::Pin(ErrorKind::Other)));
+    assert_eq!(reg(bus, ADDR, 0x1B), 0x00);
+
+    // FF_IA | SLEEP_STATE
+    assert_eq!(
+        MotionEvent::from_src(0x30),
+        Some(MotionEvent {
+            free_fall: true,
+            wake_up: None,
+            sleep_change: false,
+            sleeping: true,
+        })
+    );
+    assert_eq!(MotionEvent::from_src(0x14), None);
+}
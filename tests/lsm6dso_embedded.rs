@@ -0,0 +1,137 @@
+mod common;
+
+use common::{bank_reg, bus, lsm6dso, reg, with_device, SimPin, EMB_FUNC_BANK};
+use embassy_futures::block_on;
+use embedded_hal::digital::ErrorKind;
+use sensor_pack::lsm6dso::{EmbeddedEvents, EmbeddedFunction, Error, InterruptPin, Lsm6dso};
+use sensor_pack::SlaveAddr;
+
+const ADDR: u8 = 0x6B;
+
+#[test]
+fn enable_embedded_functions() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+
+    for function in [
+        EmbeddedFunction::Pedometer,
+        EmbeddedFunction::SignificantMotion,
+        EmbeddedFunction::Tilt,
+    ] {
+        assert_eq!(
+            block_on(sensor.enable_embedded_function(function, true)),
+            Ok(())
+        );
+    }
+    // EMB_FUNC_EN_A: SIGN_MOTION_EN | TILT_EN | PEDO_EN
+    assert_eq!(bank_reg(bus, ADDR, EMB_FUNC_BANK, 0x04), 0x38);
+    assert_eq!(
+        block_on(sensor.enable_embedded_function(EmbeddedFunction::Tilt, false)),
+        Ok(())
+    );
+    assert_eq!(bank_reg(bus, ADDR, EMB_FUNC_BANK, 0x04), 0x28);
+    // Back on the user bank, main page registers untouched
+    assert_eq!(reg(bus, ADDR, 0x01), 0x00);
+    assert_eq!(reg(bus, ADDR, 0x04), 0x00);
+    assert_eq!(block_on(sensor.check_device_id()), Ok(()));
+
+    assert_eq!(block_on(sensor.latch_embedded_interrupts(true)), Ok(()));
+    assert_eq!(bank_reg(bus, ADDR, EMB_FUNC_BANK, 0x17), 0x80);
+    // TAP_CFG0: INT_CLR_ON_READ
+    assert_eq!(reg(bus, ADDR, 0x56), 0x40);
+    assert_eq!(block_on(sensor.latch_embedded_interrupts(false)), Ok(()));
+    assert_eq!(bank_reg(bus, ADDR, EMB_FUNC_BANK, 0x17), 0x00);
+    assert_eq!(reg(bus, ADDR, 0x56), 0x40);
+}
+
+#[test]
+fn step_counter() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+
+    with_device(bus, ADDR, |d| {
+        d.banks[EMB_FUNC_BANK][0x62] = 0x34;
+        d.banks[EMB_FUNC_BANK][0x63] = 0x12;
+        // Same addresses on the main page
+        d.regs[0x62] = 0xFF;
+        d.regs[0x63] = 0xFF;
+    });
+    assert_eq!(block_on(sensor.step_count()), Ok(0x1234));
+
+    assert_eq!(block_on(sensor.reset_step_count()), Ok(()));
+    // EMB_FUNC_SRC: PEDO_RST_STEP
+    assert_eq!(bank_reg(bus, ADDR, EMB_FUNC_BANK, 0x64), 0x80);
+    assert_eq!(reg(bus, ADDR, 0x01), 0x00);
+}
+
+#[test]
+fn embedded_events() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+
+    assert_eq!(
+        block_on(sensor.route_embedded_function(
+            InterruptPin::Int2,
+            EmbeddedFunction::Pedometer,
+            true
+        )),
+        Ok(())
+    );
+    // EMB_FUNC_INT2: INT2_STEP_DETECTOR, MD2_CFG: INT2_EMB_FUNC
+    assert_eq!(bank_reg(bus, ADDR, EMB_FUNC_BANK, 0x0E), 0x08);
+    assert_eq!(reg(bus, ADDR, 0x5F), 0x02);
+    assert_eq!(
+        block_on(sensor.route_embedded_function(
+            InterruptPin::Int2,
+            EmbeddedFunction::SignificantMotion,
+            true
+        )),
+        Ok(())
+    );
+    assert_eq!(
+        block_on(sensor.route_embedded_function(
+            InterruptPin::Int2,
+            EmbeddedFunction::Pedometer,
+            false
+        )),
+        Ok(())
+    );
+    assert_eq!(bank_reg(bus, ADDR, EMB_FUNC_BANK, 0x0E), 0x20);
+    assert_eq!(reg(bus, ADDR, 0x5F), 0x02);
+    assert_eq!(
+        block_on(sensor.route_embedded_function(
+            InterruptPin::Int2,
+            EmbeddedFunction::SignificantMotion,
+            false
+        )),
+        Ok(())
+    );
+    assert_eq!(reg(bus, ADDR, 0x5F), 0x00);
+
+    assert_eq!(
+        block_on(sensor.embedded_events()),
+        Ok(EmbeddedEvents::default())
+    );
+    // EMB_FUNC_STATUS_MAINPAGE: IS_SIGMOT | IS_STEP_DET, latched and still
+    // asserted on the second wait
+    assert_eq!(block_on(sensor.latch_embedded_interrupts(true)), Ok(()));
+    with_device(bus, ADDR, |d| d.regs[0x35] = 0x28);
+    let mut pin = SimPin {
+        assertions: Some(2),
+        ..SimPin::default()
+    };
+    assert_eq!(
+        block_on(sensor.wait_for_embedded_events(&mut pin)),
+        Ok(EmbeddedEvents {
+            step: true,
+            significant_motion: true,
+            tilt: false,
+            fsm_long_counter: false,
+        })
+    );
+    // Reading EMB_FUNC_STATUS cleared the events, so they are reported once
+    assert_eq!(
+        block_on(sensor.wait_for_embedded_events(&mut pin)),
+        Err(Error::Pin(ErrorKind::Other))
+    );
+}
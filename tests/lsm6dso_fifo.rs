@@ -0,0 +1,140 @@
+mod common;
+
+use common::{bus, lsm6dso, reg, with_device};
+use embassy_futures::block_on;
+use sensor_pack::lsm6dso::{
+    AccBdr, AccOdr, AccScale, FifoConfig, FifoMode, FifoSample, FifoStatus, GyrBdr, GyrOdr,
+    GyrScale, InterruptPin, Lsm6dso, TemperatureBdr, TimestampBatch,
+};
+use sensor_pack::measurement::{Acceleration, AngularRate, Temperature};
+use sensor_pack::SlaveAddr;
+
+const ADDR: u8 = 0x6B;
+
+#[test]
+fn configure_fifo() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+
+    assert_eq!(
+        block_on(sensor.configure_fifo(&FifoConfig::default())),
+        Ok(())
+    );
+    assert_eq!(reg(bus, ADDR, 0x07), 0x00);
+    assert_eq!(reg(bus, ADDR, 0x08), 0x00);
+    // 104 Hz gyroscope and accelerometer batching
+    assert_eq!(reg(bus, ADDR, 0x09), 0x44);
+    // Continuous mode
+    assert_eq!(reg(bus, ADDR, 0x0A), 0x06);
+    assert_eq!(reg(bus, ADDR, 0x19), 0x00);
+}
+
+#[test]
+fn configure_custom_fifo() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    // FIFO_CTRL2: ODRCHG_EN, set outside of the configuration
+    with_device(bus, ADDR, |d| d.regs[0x08] = 0x10);
+    let config = FifoConfig::default()
+        .mode(FifoMode::Fifo)
+        .acc_bdr(AccBdr::KHz6_667)
+        .gyr_bdr(GyrBdr::Hz6_5)
+        .temperature_bdr(TemperatureBdr::Hz52)
+        .timestamp(TimestampBatch::Every8)
+        .watermark(300)
+        .stop_on_watermark(true);
+
+    assert_eq!(block_on(sensor.configure_fifo(&config)), Ok(()));
+    assert_eq!(reg(bus, ADDR, 0x07), 0x2C);
+    assert_eq!(reg(bus, ADDR, 0x08), 0x91);
+    assert_eq!(reg(bus, ADDR, 0x09), 0xBA);
+    assert_eq!(reg(bus, ADDR, 0x0A), 0xB1);
+    // TIMESTAMP_EN
+    assert_eq!(reg(bus, ADDR, 0x19), 0x20);
+
+    assert_eq!(block_on(sensor.set_fifo_mode(FifoMode::Bypass)), Ok(()));
+    assert_eq!(reg(bus, ADDR, 0x0A), 0xB0);
+
+    // Saturated at 9 bits
+    assert_eq!(FifoConfig::default().watermark(1000).fifo_ctrl1(), 0xFF);
+    assert_eq!(FifoConfig::default().watermark(1000).fifo_ctrl2(), 0x01);
+}
+
+#[test]
+fn fifo_watermark_interrupt() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+
+    assert_eq!(
+        block_on(sensor.route_fifo_watermark(InterruptPin::Int2, true)),
+        Ok(())
+    );
+    assert_eq!(reg(bus, ADDR, 0x0E), 0x08);
+
+    // FIFO_STATUS2: FIFO_WTM_IA, FIFO_OVR_LATCHED, DIFF_FIFO[9:8] = 1
+    with_device(bus, ADDR, |d| {
+        d.regs[0x3A] = 0x20;
+        d.regs[0x3B] = 0x89;
+    });
+    assert_eq!(
+        block_on(sensor.fifo_status()),
+        Ok(FifoStatus {
+            level: 0x120,
+            watermark: true,
+            overrun: false,
+            full: false,
+        })
+    );
+}
+
+#[test]
+fn read_fifo() {
+    let bus = bus(vec![lsm6dso()]);
+    let mut sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    block_on(sensor.init_accelerometer(AccOdr::Hz104, AccScale::G4)).unwrap();
+    block_on(sensor.init_gyroscope(GyrOdr::Hz104, GyrScale::Dps500)).unwrap();
+
+    with_device(bus, ADDR, |d| {
+        // Gyroscope, G = (1000, -1000, 0)
+        d.push_fifo(&[0x0A, 0xE8, 0x03, 0x18, 0xFC, 0x00, 0x00]);
+        // Accelerometer, A = (8197, 0, -8197)
+        d.push_fifo(&[0x12, 0x05, 0x20, 0x00, 0x00, 0xFB, 0xDF]);
+        // Temperature, T = 512
+        d.push_fifo(&[0x19, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00]);
+        // Timestamp
+        d.push_fifo(&[0x20, 0x78, 0x56, 0x34, 0x12, 0x00, 0x00]);
+        // Step counter
+        d.push_fifo(&[0x90, 0x05, 0x00, 0x10, 0x27, 0x00, 0x00]);
+    });
+    assert_eq!(block_on(sensor.fifo_status()).unwrap().level, 5);
+
+    let mut samples = [FifoSample::default(); 4];
+    assert_eq!(block_on(sensor.read_fifo(&mut samples)), Ok(4));
+    assert_eq!(
+        samples[0],
+        FifoSample::Gyro(AngularRate {
+            x: 17.5,
+            y: -17.5,
+            z: 0.0
+        })
+    );
+    let FifoSample::Accel(Acceleration { x, y, z }) = samples[1] else {
+        panic!("expected accelerometer sample, got {:?}", samples[1]);
+    };
+    assert!((x - 1.0).abs() < 1e-3);
+    assert_eq!(y, 0.0);
+    assert!((z + 1.0).abs() < 1e-3);
+    assert_eq!(samples[2], FifoSample::Temperature(Temperature(27.0)));
+    assert_eq!(samples[3], FifoSample::Timestamp(0x1234_5678));
+
+    assert_eq!(block_on(sensor.fifo_status()).unwrap().level, 1);
+    assert_eq!(block_on(sensor.read_fifo(&mut samples)), Ok(1));
+    assert_eq!(
+        samples[0],
+        FifoSample::Other {
+            tag: 0x12,
+            data: [0x05, 0x00, 0x10, 0x27, 0x00, 0x00],
+        }
+    );
+    assert_eq!(block_on(sensor.read_fifo(&mut samples)), Ok(0));
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use common::{bus, lsm6dso, reg, with_device};
+use embassy_futures::block_on;
+use sensor_pack::lsm6dso::{
+    AccBandwidth, AccOdr, Error, FilterConfig, GyrOdr, GyroHpfCutoff, GyroLpfLevel, Lsm6dso,
+};
+use sensor_pack::SlaveAddr;
+
+const ADDR: u8 = 0x6B;
+
+#[test]
+fn configure_filters() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    // CTRL1_XL: 104 Hz, CTRL2_G: 6.66 kHz
+    with_device(bus, ADDR, |d| {
+        d.regs[0x10] = 0x40;
+        d.regs[0x11] = 0xA0;
+    });
+    let config = FilterConfig::default()
+        .acc_bandwidth(Some(AccBandwidth::LowOdr20))
+        .gyr_lpf(Some(GyroLpfLevel::LevelThree))
+        .gyr_hpf(Some(GyroHpfCutoff::MilliHz65));
+
+    assert_eq!(block_on(sensor.configure_filters(&config)), Ok(()));
+    // LPF2_XL_EN, ODR untouched
+    assert_eq!(reg(bus, ADDR, 0x10), 0x42);
+    // LPF1_SEL_G, FTYPE = 010
+    assert_eq!(reg(bus, ADDR, 0x13), 0x02);
+    assert_eq!(reg(bus, ADDR, 0x15), 0x02);
+    // HP_EN_G, 65 mHz
+    assert_eq!(reg(bus, ADDR, 0x16), 0x50);
+    // HPCF_XL = ODR/20
+    assert_eq!(reg(bus, ADDR, 0x17), 0x40);
+
+    let config = FilterConfig::default()
+        .acc_bandwidth(Some(AccBandwidth::HighOdr100))
+        .acc_hp_reference(true);
+    assert_eq!(block_on(sensor.configure_filters(&config)), Ok(()));
+    // High-pass path without LPF2, everything else back to bypass
+    assert_eq!(reg(bus, ADDR, 0x10), 0x40);
+    assert_eq!(reg(bus, ADDR, 0x13), 0x00);
+    assert_eq!(reg(bus, ADDR, 0x16), 0x00);
+    assert_eq!(reg(bus, ADDR, 0x17), 0x94);
+}
+
+#[test]
+fn reject_invalid_filters() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    // CTRL2_G: 3.33 kHz
+    with_device(bus, ADDR, |d| d.regs[0x11] = 0x90);
+
+    // Reference mode needs the high-pass path
+    let reference = FilterConfig::default()
+        .acc_bandwidth(Some(AccBandwidth::LowOdr4))
+        .acc_hp_reference(true);
+    assert!(!reference.is_valid(GyrOdr::PowerDown));
+    assert_eq!(
+        block_on(sensor.configure_filters(&reference)),
+        Err(Error::InvalidFilterConfig)
+    );
+
+    // Level five is not available above 1.66 kHz
+    let level_five = FilterConfig::default().gyr_lpf(Some(GyroLpfLevel::LevelFive));
+    assert!(level_five.is_valid(GyrOdr::KHz1_66));
+    assert!(!level_five.is_valid(GyrOdr::KHz3_33));
+    assert_eq!(
+        block_on(sensor.configure_filters(&level_five)),
+        Err(Error::InvalidFilterConfig)
+    );
+    // Nothing written
+    assert!(with_device(bus, ADDR, |d| d.writes.is_empty()));
+}
+
+#[test]
+fn effective_cutoff() {
+    let default = FilterConfig::default();
+    assert_eq!(default.acc_cutoff_hz(AccOdr::Hz104), 52.0);
+    assert_eq!(default.gyr_lpf_cutoff_hz(GyrOdr::Hz104), Some(33.0));
+    assert_eq!(default.gyr_lpf_cutoff_hz(GyrOdr::Hz833), None);
+    assert_eq!(default.gyr_hpf_cutoff_hz(), None);
+
+    let config = FilterConfig::default()
+        .acc_bandwidth(Some(AccBandwidth::LowOdr45))
+        .gyr_lpf(Some(GyroLpfLevel::LevelSix))
+        .gyr_hpf(Some(GyroHpfCutoff::Hz1));
+    assert_eq!(config.acc_cutoff_hz(AccOdr::KHz1_66), 1660.0 / 45.0);
+    assert_eq!(config.gyr_lpf_cutoff_hz(GyrOdr::Hz833), Some(49.4));
+    assert_eq!(config.gyr_lpf_cutoff_hz(GyrOdr::KHz6_66), None);
+    assert_eq!(config.gyr_lpf_cutoff_hz(GyrOdr::PowerDown), None);
+    assert_eq!(config.gyr_hpf_cutoff_hz(), Some(1.04));
+}
@@ -0,0 +1,170 @@
+mod common;
+
+use common::{bank_reg, bus, lsm6dso, reg, with_device, SimPin, EMB_FUNC_BANK};
+use core::pin::pin;
+use embassy_futures::{block_on, poll_once};
+use embassy_time::{Duration, MockDriver};
+use embedded_hal::digital::ErrorKind;
+use sensor_pack::lsm6dso::{Error, FsmOdr, InterruptPin, Lsm6dso, UcfLine};
+use sensor_pack::SlaveAddr;
+
+const ADDR: u8 = 0x6B;
+
+#[test]
+fn parse_ucf() {
+    let program: Vec<UcfLine> =
+        "--Launch detect\nAc 01 80\n\nAc 46 0F\nWAIT 5\nAc 7z 00\nAc 01 00 00\nWAIT\nAc 01 00\n"
+            .lines()
+            .filter_map(UcfLine::parse)
+            .collect();
+    assert_eq!(
+        program,
+        [
+            UcfLine::Write {
+                address: 0x01,
+                data: 0x80
+            },
+            UcfLine::Write {
+                address: 0x46,
+                data: 0x0F
+            },
+            UcfLine::Wait(5),
+            UcfLine::Write {
+                address: 0x01,
+                data: 0x00
+            },
+        ]
+    );
+}
+
+#[test]
+fn load_ucf() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    // Ends without switching back to the user bank
+    let program = [
+        UcfLine::Write {
+            address: 0x10,
+            data: 0x28,
+        },
+        UcfLine::Wait(5),
+        UcfLine::Write {
+            address: 0x01,
+            data: 0x80,
+        },
+        UcfLine::Write {
+            address: 0x46,
+            data: 0x03,
+        },
+        UcfLine::Write {
+            address: 0x05,
+            data: 0x01,
+        },
+    ];
+
+    // Held at the wait until 5 ms have passed
+    let mut load = pin!(sensor.load_ucf(&program));
+    assert!(poll_once(load.as_mut()).is_pending());
+    MockDriver::get().advance(Duration::from_millis(4));
+    assert!(poll_once(load.as_mut()).is_pending());
+    MockDriver::get().advance(Duration::from_millis(1));
+    assert_eq!(block_on(load), Ok(()));
+
+    assert_eq!(reg(bus, ADDR, 0x10), 0x28);
+
+    assert_eq!(bank_reg(bus, ADDR, EMB_FUNC_BANK, 0x46), 0x03);
+    assert_eq!(bank_reg(bus, ADDR, EMB_FUNC_BANK, 0x05), 0x01);
+    assert_eq!(reg(bus, ADDR, 0x01), 0x00);
+    assert_eq!(reg(bus, ADDR, 0x46), 0x00);
+}
+
+#[test]
+fn configure_fsm() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+
+    assert_eq!(block_on(sensor.enable_fsm(0x0201)), Ok(()));
+    assert_eq!(bank_reg(bus, ADDR, EMB_FUNC_BANK, 0x46), 0x01);
+    assert_eq!(bank_reg(bus, ADDR, EMB_FUNC_BANK, 0x47), 0x02);
+    // EMB_FUNC_EN_B: FSM_EN
+    assert_eq!(bank_reg(bus, ADDR, EMB_FUNC_BANK, 0x05), 0x01);
+    assert_eq!(block_on(sensor.enable_fsm(0)), Ok(()));
+    assert_eq!(bank_reg(bus, ADDR, EMB_FUNC_BANK, 0x05), 0x00);
+
+    assert_eq!(block_on(sensor.set_fsm_odr(FsmOdr::Hz104)), Ok(()));
+    assert_eq!(bank_reg(bus, ADDR, EMB_FUNC_BANK, 0x5F), 0x5B);
+
+    with_device(bus, ADDR, |d| d.writes.clear());
+    assert_eq!(
+        block_on(sensor.set_fsm_long_counter_timeout(0x1234)),
+        Ok(())
+    );
+    assert_eq!(
+        with_device(bus, ADDR, |d| d.writes.clone()),
+        [
+            // FUNC_CFG_ACCESS
+            (0x01, 0x80),
+            // PAGE_RW: PAGE_WRITE
+            (0x17, 0x40),
+            // PAGE_SEL: page 1
+            (0x02, 0x11),
+            // PAGE_ADDRESS: FSM_LC_TIMEOUT_L
+            (0x08, 0x7A),
+            (0x09, 0x34),
+            (0x09, 0x12),
+            (0x02, 0x01),
+            (0x17, 0x00),
+            (0x01, 0x00),
+        ]
+    );
+}
+
+#[test]
+fn fsm_events() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+
+    assert_eq!(
+        block_on(sensor.route_fsm(InterruptPin::Int1, 0x8001)),
+        Ok(())
+    );
+    assert_eq!(bank_reg(bus, ADDR, EMB_FUNC_BANK, 0x0B), 0x01);
+    assert_eq!(bank_reg(bus, ADDR, EMB_FUNC_BANK, 0x0C), 0x80);
+    // MD1_CFG: INT1_EMB_FUNC
+    assert_eq!(reg(bus, ADDR, 0x5E), 0x02);
+    assert_eq!(block_on(sensor.route_fsm(InterruptPin::Int1, 0)), Ok(()));
+    assert_eq!(reg(bus, ADDR, 0x5E), 0x00);
+
+    with_device(bus, ADDR, |d| {
+        // FSM_OUTS1..16
+        for (i, out) in d.banks[EMB_FUNC_BANK][0x4C..0x5C].iter_mut().enumerate() {
+            *out = i as u8;
+        }
+        // FSM_LONG_COUNTER
+        d.banks[EMB_FUNC_BANK][0x48] = 0x02;
+        d.banks[EMB_FUNC_BANK][0x49] = 0x01;
+    });
+    let outputs = block_on(sensor.fsm_outputs()).unwrap();
+    assert_eq!(outputs[0], 0);
+    assert_eq!(outputs[15], 15);
+    assert_eq!(block_on(sensor.fsm_long_counter()), Ok(0x0102));
+    assert_eq!(block_on(sensor.reset_fsm_long_counter()), Ok(()));
+    // FSM_LONG_COUNTER_CLEAR: FSM_LC_CLEAR
+    assert_eq!(bank_reg(bus, ADDR, EMB_FUNC_BANK, 0x4A), 0x01);
+
+    assert_eq!(block_on(sensor.fsm_status()), Ok(0));
+    // FSM_STATUS_B_MAINPAGE: IS_FSM10, latched and still asserted on the
+    // second wait
+    assert_eq!(block_on(sensor.latch_embedded_interrupts(true)), Ok(()));
+    with_device(bus, ADDR, |d| d.regs[0x37] = 0x02);
+    let mut pin = SimPin {
+        assertions: Some(2),
+        ..SimPin::default()
+    };
+    assert_eq!(block_on(sensor.wait_for_fsm(&mut pin)), Ok(0x0200));
+    // Reading FSM_STATUS cleared the event, so it is reported once
+    assert_eq!(
+        block_on(sensor.wait_for_fsm(&mut pin)),
+        Err(Error::Pin(ErrorKind::Other))
+    );
+}
@@ -0,0 +1,49 @@
+mod common;
+
+use common::{bus, lsm6dso, reg, with_device};
+use embassy_futures::block_on;
+use sensor_pack::lsm6dso::{AccOffsetConfig, Lsm6dso};
+use sensor_pack::SlaveAddr;
+
+const ADDR: u8 = 0x6B;
+
+#[test]
+fn configure_fine_offset() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    // WAKE_UP_THS: WK_THS set outside of the configuration
+    with_device(bus, ADDR, |d| d.regs[0x5B] = 0x02);
+    let config = AccOffsetConfig::default().offset_mg([10.0, -20.0, 124.0]);
+
+    assert_eq!(block_on(sensor.configure_acc_offset(&config)), Ok(()));
+    // 2^-10 g weight: 10, -20 and 127 LSB
+    assert_eq!(reg(bus, ADDR, 0x15), 0x00);
+    assert_eq!(reg(bus, ADDR, 0x73), 10);
+    assert_eq!(reg(bus, ADDR, 0x74), (-20i8) as u8);
+    assert_eq!(reg(bus, ADDR, 0x75), 127);
+    // USR_OFF_ON_OUT
+    assert_eq!(reg(bus, ADDR, 0x16), 0x02);
+    assert_eq!(reg(bus, ADDR, 0x5B), 0x02);
+}
+
+#[test]
+fn configure_coarse_offset() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    let config = AccOffsetConfig::default()
+        .offset_mg([500.0, -3000.0, 0.0])
+        .output(false)
+        .wake_up(true);
+
+    assert_eq!(block_on(sensor.configure_acc_offset(&config)), Ok(()));
+    // USR_OFF_W, 2^-6 g weight: 32 LSB and saturated at -127 LSB
+    assert_eq!(reg(bus, ADDR, 0x15), 0x08);
+    assert_eq!(reg(bus, ADDR, 0x73), 32);
+    assert_eq!(reg(bus, ADDR, 0x74), (-127i8) as u8);
+    assert_eq!(reg(bus, ADDR, 0x75), 0);
+    // Wake-up only
+    assert_eq!(reg(bus, ADDR, 0x16), 0x00);
+    assert_eq!(reg(bus, ADDR, 0x5B), 0x40);
+    assert_eq!(config.weight_mg(), 15.625);
+    assert_eq!(config.applied_mg(), [500.0, -1984.375, 0.0]);
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::{bus, lsm6dso, reg, spi_bus, spi_reg, with_spi_device};
+use embassy_futures::block_on;
+use sensor_pack::lsm6dso::{
+    AccScale, Error, GyrScale, GyroHpfCutoff, Lsm6dso, Lsm6dsoOis, OisAccBandwidth, OisConfig,
+    OisGyrLpf,
+};
+use sensor_pack::measurement::{Acceleration, AngularRate};
+use sensor_pack::SlaveAddr;
+
+const ADDR: u8 = 0x6B;
+
+#[test]
+fn configure_ois() {
+    let bus = spi_bus(lsm6dso());
+    let mut ois = Lsm6dsoOis::new(bus);
+
+    assert_eq!(block_on(ois.configure(&OisConfig::default())), Ok(()));
+    // OIS_EN_SPI2, 250 dps
+    assert_eq!(spi_reg(bus, 0x70), 0x01);
+    assert_eq!(spi_reg(bus, 0x71), 0x00);
+    assert_eq!(spi_reg(bus, 0x72), 0x00);
+
+    let config = OisConfig::default()
+        .gyr_scale(GyrScale::Dps125)
+        .gyr_lpf(OisGyrLpf::Hz470)
+        .gyr_hpf(Some(GyroHpfCutoff::MilliHz260))
+        .accelerometer(true)
+        .acc_scale(AccScale::G8)
+        .acc_bandwidth(OisAccBandwidth::Hz65_2);
+    assert_eq!(block_on(ois.configure(&config)), Ok(()));
+    // Mode4_EN, FS_125_OIS, OIS_EN_SPI2
+    assert_eq!(spi_reg(bus, 0x70), 0x13);
+    // 470 Hz low-pass, 260 mHz high-pass
+    assert_eq!(spi_reg(bus, 0x71), 0x27);
+    // 8 g, 65.2 Hz
+    assert_eq!(spi_reg(bus, 0x72), 0xD8);
+
+    assert_eq!(block_on(ois.route_data_ready(true)), Ok(()));
+    assert_eq!(spi_reg(bus, 0x6F), 0x80);
+}
+
+#[test]
+fn ois_sample() {
+    let bus = spi_bus(lsm6dso());
+    let mut ois = Lsm6dsoOis::new(bus);
+    let config = OisConfig::default()
+        .gyr_scale(GyrScale::Dps2000)
+        .accelerometer(true)
+        .acc_scale(AccScale::G4);
+    assert_eq!(block_on(ois.configure(&config)), Ok(()));
+
+    // STATUS_SPIAux: gyroscope still settling
+    with_spi_device(bus, |d| d.regs[0x1E] = 0x06);
+    assert_eq!(block_on(ois.sample()), Err(Error::DataNotReady));
+
+    // 1000 counts on X of both sensors, -1000 on Z
+    with_spi_device(bus, |d| {
+        d.regs[0x1E] = 0x03;
+        d.set_output(&[
+            0, 0, 0xE8, 0x03, 0, 0, 0x18, 0xFC, 0xE8, 0x03, 0, 0, 0x18, 0xFC,
+        ]);
+    });
+    assert_eq!(
+        block_on(ois.sample()).map(|s| (s.angular_rate, s.acceleration)),
+        Ok((
+            AngularRate {
+                x: 70.0,
+                y: 0.0,
+                z: -70.0
+            },
+            Acceleration {
+                x: 0.122,
+                y: 0.0,
+                z: -0.122
+            }
+        ))
+    );
+}
+
+#[test]
+fn enable_ois_from_primary() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+
+    assert_eq!(block_on(sensor.enable_ois(true)), Ok(()));
+    // OIS_ON_EN, OIS_ON
+    assert_eq!(reg(bus, ADDR, 0x16), 0x05);
+    assert_eq!(block_on(sensor.enable_ois(false)), Ok(()));
+    // The primary interface keeps control of the chain
+    assert_eq!(reg(bus, ADDR, 0x16), 0x04);
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use common::{bus, lsm6dso, reg, with_device, SimPin};
+use embassy_futures::block_on;
+use embedded_hal::digital::ErrorKind;
+use sensor_pack::lsm6dso::{
+    Error, InterruptPin, Lsm6dso, Orientation, OrientationConfig, SixdThreshold,
+};
+use sensor_pack::SlaveAddr;
+
+const ADDR: u8 = 0x6B;
+
+#[test]
+fn configure_orientation() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    // TAP_THS_6D: TAP_THS_Z = 8, set outside of the configuration
+    with_device(bus, ADDR, |d| d.regs[0x59] = 0x08);
+
+    assert_eq!(
+        block_on(sensor.configure_orientation(&OrientationConfig::default())),
+        Ok(())
+    );
+    assert_eq!(reg(bus, ADDR, 0x59), 0x48);
+    assert_eq!(reg(bus, ADDR, 0x17), 0x00);
+    // INTERRUPTS_ENABLE
+    assert_eq!(reg(bus, ADDR, 0x58), 0x80);
+
+    let config = OrientationConfig::default()
+        .threshold(SixdThreshold::Deg50)
+        .four_d(true)
+        .low_pass(true);
+    assert_eq!(block_on(sensor.configure_orientation(&config)), Ok(()));
+    assert_eq!(reg(bus, ADDR, 0x59), 0xE8);
+    assert_eq!(reg(bus, ADDR, 0x17), 0x01);
+}
+
+#[test]
+fn orientation_change() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+
+    assert_eq!(
+        block_on(sensor.route_orientation(InterruptPin::Int2, true)),
+        Ok(())
+    );
+    assert_eq!(reg(bus, ADDR, 0x5F), 0x04);
+
+    assert_eq!(block_on(sensor.orientation()), Ok(None));
+    // D6D_SRC: ZL
+    with_device(bus, ADDR, |d| d.regs[0x1D] = 0x10);
+    assert_eq!(block_on(sensor.orientation()), Ok(Some(Orientation::ZDown)));
+    // No change without D6D_IA
+    assert_eq!(block_on(sensor.read_orientation_event()), Ok(None));
+    // D6D_IA | ZL
+    with_device(bus, ADDR, |d| d.regs[0x1D] = 0x50);
+    assert_eq!(
+        block_on(sensor.read_orientation_event()),
+        Ok(Some(Orientation::ZDown))
+    );
+
+    // D6D_IA | YH, latched and still asserted on the second wait
+    assert_eq!(block_on(sensor.latch_interrupts(true)), Ok(()));
+    with_device(bus, ADDR, |d| d.regs[0x1D] = 0x48);
+    let mut pin = SimPin {
+        assertions: Some(2),
+        ..SimPin::default()
+    };
+    assert_eq!(
+        block_on(sensor.wait_for_orientation(&mut pin)),
+        Ok(Orientation::YUp)
+    );
+    assert_eq!(pin.waits, 1);
+    // Reading D6D_SRC cleared the change, so it is reported once
+    assert_eq!(
+        block_on(sensor.wait_for_orientation(&mut pin)),
+        Err(Error::Pin(ErrorKind::Other))
+    );
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use common::{bus, lsm6dso, reg, with_device};
+use embassy_futures::block_on;
+use sensor_pack::lsm6dso::{AccMode, Error, GyrMode, GyrOdr, GyrScale, Lsm6dso};
+use sensor_pack::SlaveAddr;
+
+const ADDR: u8 = 0x6B;
+
+#[test]
+fn acc_modes() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    // CTRL1_XL: 52 Hz, 4 g
+    with_device(bus, ADDR, |d| d.regs[0x10] = 0x38);
+
+    assert_eq!(block_on(sensor.set_acc_mode(AccMode::LowPower)), Ok(()));
+    // XL_HM_MODE
+    assert_eq!(reg(bus, ADDR, 0x15), 0x10);
+    // 52 Hz is a low-power ODR
+    assert_eq!(
+        block_on(sensor.set_acc_mode(AccMode::Normal)),
+        Err(Error::InvalidPowerMode)
+    );
+
+    assert_eq!(
+        block_on(sensor.set_acc_mode(AccMode::UltraLowPower)),
+        Ok(())
+    );
+    // XL_ULP_EN, XL_HM_MODE cleared, entered from power-down
+    assert_eq!(reg(bus, ADDR, 0x14), 0x80);
+    assert_eq!(reg(bus, ADDR, 0x15), 0x00);
+    assert_eq!(reg(bus, ADDR, 0x10), 0x38);
+    let writes = with_device(bus, ADDR, |d| d.writes.clone());
+    let odr: Vec<u8> = writes
+        .iter()
+        .filter(|(r, _)| *r == 0x10)
+        .map(|(_, v)| *v)
+        .collect();
+    assert_eq!(odr[odr.len() - 2..], [0x08, 0x38]);
+
+    assert_eq!(block_on(sensor.set_acc_mode(AccMode::HighPerf)), Ok(()));
+    assert_eq!(reg(bus, ADDR, 0x14), 0x00);
+    assert_eq!(reg(bus, ADDR, 0x10), 0x38);
+
+    assert_eq!(block_on(sensor.set_acc_mode(AccMode::PowerDown)), Ok(()));
+    // Full scale kept
+    assert_eq!(reg(bus, ADDR, 0x10), 0x08);
+}
+
+#[test]
+fn ultra_low_power_excludes_gyro() {
+    let bus = bus(vec![lsm6dso()]);
+    let mut sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    // CTRL1_XL: 26 Hz, CTRL2_G: 104 Hz
+    with_device(bus, ADDR, |d| {
+        d.regs[0x10] = 0x20;
+        d.regs[0x11] = 0x40;
+    });
+
+    assert_eq!(
+        block_on(sensor.set_acc_mode(AccMode::UltraLowPower)),
+        Err(Error::InvalidPowerMode)
+    );
+    assert!(with_device(bus, ADDR, |d| d.writes.is_empty()));
+
+    assert_eq!(block_on(sensor.set_gyro_mode(GyrMode::PowerDown)), Ok(()));
+    assert_eq!(reg(bus, ADDR, 0x11), 0x00);
+    assert_eq!(
+        block_on(sensor.set_acc_mode(AccMode::UltraLowPower)),
+        Ok(())
+    );
+
+    // The gyroscope cannot be woken up, even into sleep
+    assert_eq!(
+        block_on(sensor.set_gyro_mode(GyrMode::Sleep)),
+        Err(Error::InvalidPowerMode)
+    );
+    assert_eq!(
+        block_on(sensor.set_gyro_mode(GyrMode::HighPerf)),
+        Err(Error::InvalidPowerMode)
+    );
+
+    // Nor started by setting its ODR
+    assert_eq!(
+        block_on(sensor.init_gyroscope(GyrOdr::Hz104, GyrScale::Dps500)),
+        Err(Error::InvalidPowerMode)
+    );
+    assert_eq!(reg(bus, ADDR, 0x11), 0x00);
+    assert_eq!(
+        block_on(sensor.init_gyroscope(GyrOdr::PowerDown, GyrScale::Dps500)),
+        Ok(())
+    );
+    assert_eq!(reg(bus, ADDR, 0x11), 0x04);
+}
+
+#[test]
+fn gyro_modes() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    // CTRL2_G: 208 Hz, 500 dps
+    with_device(bus, ADDR, |d| d.regs[0x11] = 0x54);
+
+    assert_eq!(
+        block_on(sensor.set_gyro_mode(GyrMode::LowPower)),
+        Err(Error::InvalidPowerMode)
+    );
+    assert_eq!(block_on(sensor.set_gyro_mode(GyrMode::Normal)), Ok(()));
+    // G_HM_MODE
+    assert_eq!(reg(bus, ADDR, 0x16), 0x80);
+
+    assert_eq!(block_on(sensor.set_gyro_mode(GyrMode::Sleep)), Ok(()));
+    // SLEEP_G, ODR and normal mode kept for the wake-up
+    assert_eq!(reg(bus, ADDR, 0x13), 0x40);
+    assert_eq!(reg(bus, ADDR, 0x16), 0x80);
+    assert_eq!(reg(bus, ADDR, 0x11), 0x54);
+
+    assert_eq!(block_on(sensor.set_gyro_mode(GyrMode::HighPerf)), Ok(()));
+    assert_eq!(reg(bus, ADDR, 0x13), 0x00);
+    assert_eq!(reg(bus, ADDR, 0x16), 0x00);
+}
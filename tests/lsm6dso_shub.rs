@@ -0,0 +1,141 @@
+mod common;
+
+use common::{bank_reg, bus, lsm6dso, reg, with_device, SimBus, SENSOR_HUB_BANK};
+use embassy_futures::block_on;
+use sensor_pack::lis2mdl::Lis2mdlConfig;
+use sensor_pack::lsm6dso::{
+    AccScale, Error, FifoSample, GyrScale, HubLis2mdl, Lsm6dso, SensorHubRead, SensorHubStatus,
+    ShubOdr,
+};
+use sensor_pack::measurement::MagneticField;
+use sensor_pack::{I2cTransport, SlaveAddr};
+
+const ADDR: u8 = 0x6B;
+
+#[test]
+fn configure_sensor_hub() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    let reads = [
+        SensorHubRead::new(0x1E, 0x68, 6).batch(true),
+        SensorHubRead::new(0x5D, 0x28, 12),
+    ];
+
+    assert_eq!(
+        block_on(sensor.configure_sensor_hub(&reads, ShubOdr::Hz52, true)),
+        Ok(())
+    );
+    let shub = |r| bank_reg(bus, ADDR, SENSOR_HUB_BANK, r);
+    // SLV0: read flag, 52 Hz, batched, 6 bytes
+    assert_eq!([shub(0x15), shub(0x16), shub(0x17)], [0x3D, 0x68, 0x4E]);
+    // SLV1: length saturated at 7 bytes
+    assert_eq!([shub(0x18), shub(0x19), shub(0x1A)], [0xBB, 0x28, 0x07]);
+    // Two slaves, pull-ups, master on
+    assert_eq!(shub(0x14), 0x0D);
+    // User bank restored
+    assert_eq!(reg(bus, ADDR, 0x01), 0x00);
+
+    assert_eq!(block_on(sensor.stop_sensor_hub()), Ok(()));
+    assert_eq!(bank_reg(bus, ADDR, SENSOR_HUB_BANK, 0x14), 0x09);
+}
+
+#[test]
+fn lis2mdl_through_hub() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    // STATUS_MASTER_MAINPAGE: WR_ONCE_DONE
+    with_device(bus, ADDR, |d| d.regs[0x39] = 0x80);
+    let magnetometer = sensor.lis2mdl();
+
+    assert_eq!(
+        block_on(magnetometer.init(&Lis2mdlConfig::default())),
+        Ok(())
+    );
+    // CFG_REG_A, CFG_REG_B and CFG_REG_C written once each through slave 0
+    let writes = with_device(bus, ADDR, |d| d.writes.clone());
+    let written = |r| {
+        writes
+            .iter()
+            .filter(|(reg, _)| *reg == r)
+            .map(|(_, v)| *v)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(written(0x16), [0x60, 0x61, 0x62]);
+    assert_eq!(written(0x21), [0x8C, 0x00, 0x10]);
+    assert_eq!(bank_reg(bus, ADDR, SENSOR_HUB_BANK, 0x15), 0x3C);
+    // Master stopped after the last write
+    assert_eq!(bank_reg(bus, ADDR, SENSOR_HUB_BANK, 0x14), 0x40);
+
+    assert_eq!(block_on(magnetometer.start(ShubOdr::Hz104, true)), Ok(()));
+    let shub = |r| bank_reg(bus, ADDR, SENSOR_HUB_BANK, r);
+    assert_eq!([shub(0x15), shub(0x16), shub(0x17)], [0x3D, 0x68, 0x0E]);
+    assert_eq!(shub(0x14), 0x44);
+
+    // 1000, -1000 and 2 counts at 1.5 mG/LSB
+    with_device(bus, ADDR, |d| {
+        d.banks[SENSOR_HUB_BANK][0x02..0x08].copy_from_slice(&[0xE8, 0x03, 0x18, 0xFC, 0x02, 0x00])
+    });
+    let field = block_on(magnetometer.magnetic_field()).unwrap();
+    assert!((field.x - 1.5).abs() < 1e-6);
+    assert!((field.y + 1.5).abs() < 1e-6);
+    assert!((field.z - 0.003).abs() < 1e-6);
+
+    // Batched words carry the slave number
+    assert_eq!(
+        FifoSample::decode(
+            [0x0E << 3, 0xE8, 0x03, 0, 0, 0, 0],
+            AccScale::G2,
+            GyrScale::Dps250
+        ),
+        FifoSample::SensorHub {
+            slave: 0,
+            data: [0xE8, 0x03, 0, 0, 0, 0]
+        }
+    );
+    assert_eq!(
+        HubLis2mdl::<I2cTransport<SimBus>>::decode([0xE8, 0x03, 0, 0, 0, 0]),
+        MagneticField {
+            x: 1.5,
+            y: 0.0,
+            z: 0.0
+        }
+    );
+}
+
+#[test]
+fn sensor_hub_write_nack() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    // STATUS_MASTER_MAINPAGE: SLAVE0_NACK, SLV0_CONFIG: 52 Hz, batched,
+    // 6 bytes left by a read configuration
+    with_device(bus, ADDR, |d| {
+        d.regs[0x39] = 0x08;
+        d.banks[SENSOR_HUB_BANK][0x17] = 0x4E;
+    });
+
+    assert_eq!(
+        block_on(sensor.sensor_hub_write(0x1E, 0x60, 0x00)),
+        Err(Error::SensorHubNack)
+    );
+    // Master stopped and user bank restored
+    assert_eq!(bank_reg(bus, ADDR, SENSOR_HUB_BANK, 0x14), 0x40);
+    assert_eq!(reg(bus, ADDR, 0x01), 0x00);
+    // Read length and batching off for the write, then restored
+    let slv0_config = with_device(bus, ADDR, |d| {
+        d.writes
+            .iter()
+            .filter(|(reg, _)| *reg == 0x17)
+            .map(|(_, v)| *v)
+            .collect::<Vec<_>>()
+    });
+    assert_eq!(slv0_config, [0x40, 0x4E]);
+    assert_eq!(bank_reg(bus, ADDR, SENSOR_HUB_BANK, 0x17), 0x4E);
+    assert_eq!(
+        block_on(sensor.sensor_hub_status()),
+        Ok(SensorHubStatus {
+            end_of_operation: false,
+            write_once_done: false,
+            nack: 0x01,
+        })
+    );
+}
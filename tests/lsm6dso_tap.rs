@@ -0,0 +1,141 @@
+mod common;
+
+use common::{bus, lsm6dso, reg, with_device, SimPin};
+use embassy_futures::block_on;
+use embedded_hal::digital::ErrorKind;
+use sensor_pack::lsm6dso::{
+    AccOdr, AccScale, Axis, Error, InterruptPin, Lsm6dso, TapConfig, TapEvent, TapKind, TapPriority,
+};
+use sensor_pack::SlaveAddr;
+
+const ADDR: u8 = 0x6B;
+
+#[test]
+fn configure_tap() {
+    let bus = bus(vec![lsm6dso()]);
+    let mut sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    block_on(sensor.init_accelerometer(AccOdr::Hz416, AccScale::G2)).unwrap();
+    // TAP_CFG2: INACT_EN, WAKE_UP_THS: WK_THS, set outside of the configuration
+    with_device(bus, ADDR, |d| {
+        d.regs[0x58] = 0x20;
+        d.regs[0x5B] = 0x02;
+    });
+
+    assert_eq!(
+        block_on(sensor.configure_tap(&TapConfig::default())),
+        Ok(())
+    );
+    // INT_CLR_ON_READ | TAP_X_EN | TAP_Y_EN | TAP_Z_EN | LIR
+    assert_eq!(reg(bus, ADDR, 0x56), 0x4F);
+    // 0.5 g at 2 g full scale
+    assert_eq!(reg(bus, ADDR, 0x57), 0x08);
+    assert_eq!(reg(bus, ADDR, 0x58), 0xA8);
+    assert_eq!(reg(bus, ADDR, 0x59), 0x08);
+    assert_eq!(reg(bus, ADDR, 0x5A), 0xFF);
+    assert_eq!(reg(bus, ADDR, 0x5B), 0x82);
+}
+
+#[test]
+fn configure_custom_tap() {
+    let bus = bus(vec![lsm6dso()]);
+    let mut sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    block_on(sensor.init_accelerometer(AccOdr::Hz833, AccScale::G8)).unwrap();
+    let config = TapConfig::default()
+        .axes(false, false, true)
+        .threshold(1.25)
+        .priority(TapPriority::Zyx)
+        .shock(2)
+        .quiet(1)
+        .duration(20)
+        .double_tap(false)
+        .latched(false);
+
+    assert_eq!(block_on(sensor.configure_tap(&config)), Ok(()));
+    assert_eq!(reg(bus, ADDR, 0x56), 0x02);
+    assert_eq!(reg(bus, ADDR, 0x57), 0x65);
+    assert_eq!(reg(bus, ADDR, 0x58), 0x85);
+    assert_eq!(reg(bus, ADDR, 0x59), 0x05);
+    assert_eq!(reg(bus, ADDR, 0x5A), 0xF6);
+    assert_eq!(reg(bus, ADDR, 0x5B), 0x00);
+
+    // Saturated at 5 bits
+    assert_eq!(
+        TapConfig::default().threshold(4.0).tap_cfg1(AccScale::G2),
+        0x1F
+    );
+}
+
+#[test]
+fn tap_events() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+
+    assert_eq!(
+        block_on(sensor.route_tap(InterruptPin::Int1, false, true)),
+        Ok(())
+    );
+    assert_eq!(reg(bus, ADDR, 0x5E), 0x08);
+    assert_eq!(
+        block_on(sensor.route_tap(InterruptPin::Int2, true, true)),
+        Ok(())
+    );
+    assert_eq!(reg(bus, ADDR, 0x5F), 0x48);
+
+    assert_eq!(block_on(sensor.read_tap_event()), Ok(None));
+
+    // TAP_SRC: TAP_IA | DOUBLE_TAP | TAP_SIGN | Z_TAP
+    with_device(bus, ADDR, |d| d.regs[0x1C] = 0x59);
+    let mut pin = SimPin::default();
+    let mut events = sensor.tap_events(&mut pin);
+    assert_eq!(
+        block_on(events.next()),
+        Ok(TapEvent {
+            kind: TapKind::Double,
+            axis: Axis::Z,
+            negative: true,
+        })
+    );
+    assert_eq!(pin.waits, 1);
+
+    // TAP_IA | SINGLE_TAP | X_TAP
+    assert_eq!(
+        TapEvent::from_src(0x64),
+        Some(TapEvent {
+            kind: TapKind::Single,
+            axis: Axis::X,
+            negative: false,
+        })
+    );
+    assert_eq!(TapEvent::from_src(0x24), None);
+}
+
+#[test]
+fn latched_tap_reported_once() {
+    let bus = bus(vec![lsm6dso()]);
+    let mut sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    block_on(sensor.init_accelerometer(AccOdr::Hz416, AccScale::G2)).unwrap();
+    assert_eq!(
+        block_on(sensor.configure_tap(&TapConfig::default())),
+        Ok(())
+    );
+
+    // TAP_SRC: TAP_IA | SINGLE_TAP | X_TAP. The pin is still high on the
+    // second wait, as if the latched line had not dropped yet.
+    with_device(bus, ADDR, |d| d.regs[0x1C] = 0x64);
+    let mut pin = SimPin {
+        assertions: Some(2),
+        ..SimPin::default()
+    };
+    let mut events = sensor.tap_events(&mut pin);
+    assert_eq!(
+        block_on(events.next()),
+        Ok(TapEvent {
+            kind: TapKind::Single,
+            axis: Axis::X,
+            negative: false,
+        })
+    );
+    // Reading TAP_SRC cleared the event, so the stray wake-up finds nothing
+    assert_eq!(block_on(events.next()), Err(Error::Pin(ErrorKind::Other)));
+    assert_eq!(pin.waits, 2);
+}
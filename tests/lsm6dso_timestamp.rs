@@ -0,0 +1,143 @@
+mod common;
+
+use common::{bus, lsm6dso, reg, with_device};
+use embassy_futures::block_on;
+use embassy_time::{Duration, Instant, MockDriver};
+use sensor_pack::lsm6dso::{Lsm6dso, TimestampCorrelator};
+use sensor_pack::SlaveAddr;
+
+const ADDR: u8 = 0x6B;
+
+#[test]
+fn timestamp_counter() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+
+    assert_eq!(block_on(sensor.enable_timestamp(true)), Ok(()));
+    assert_eq!(reg(bus, ADDR, 0x19), 0x20);
+
+    with_device(bus, ADDR, |d| {
+        d.regs[0x40..0x44].copy_from_slice(&[0x78, 0x56, 0x34, 0x12]);
+        // ALL_INT_SRC: TIMESTAMP_ENDCOUNT
+        d.regs[0x1A] = 0x80;
+    });
+    assert_eq!(block_on(sensor.timestamp()), Ok(0x1234_5678));
+    assert_eq!(block_on(sensor.timestamp_overflow()), Ok(true));
+
+    assert_eq!(block_on(sensor.reset_timestamp()), Ok(()));
+    assert_eq!(
+        with_device(bus, ADDR, |d| d.writes.last().copied()),
+        Some((0x42, 0xAA))
+    );
+}
+
+#[test]
+fn timestamp_resolution() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+
+    assert_eq!(block_on(sensor.timestamp_resolution()), Ok(25_000.0));
+
+    // INTERNAL_FREQ_FINE = -10, internal clock 1.5% slow
+    with_device(bus, ADDR, |d| d.regs[0x63] = 0xF6);
+    let resolution = block_on(sensor.timestamp_resolution()).unwrap();
+    assert!((resolution - 25_000.0 / 0.985).abs() < 0.01);
+}
+
+#[test]
+fn correlate_with_drift() {
+    let mut correlator = TimestampCorrelator::new(25_000.0);
+    assert_eq!(correlator.instant(0), None);
+
+    // Sensor clock 100 ppm slow
+    correlator.sync(1000, Instant::from_secs(1));
+    assert_eq!(correlator.drift_ppm(), 0.0);
+    assert_eq!(
+        correlator.instant(1400),
+        Some(Instant::from_micros(1_010_000))
+    );
+
+    correlator.sync(401_000, Instant::from_micros(11_001_000));
+    assert!((correlator.drift_ppm() - 100.0).abs() < 0.1);
+    assert_eq!(
+        correlator.instant(801_000),
+        Some(Instant::from_micros(21_002_000))
+    );
+    // Before the latest sync point
+    assert_eq!(
+        correlator.instant(1000),
+        Some(Instant::from_micros(1_000_000))
+    );
+
+    correlator.reset();
+    assert_eq!(correlator.instant(1000), None);
+    assert_eq!(correlator.drift_ppm(), 0.0);
+}
+
+#[test]
+fn correlate_across_counter_overflow() {
+    let mut correlator = TimestampCorrelator::new(25_000.0);
+
+    correlator.sync(u32::MAX - 19_999, Instant::from_secs(10));
+    correlator.sync(20_000, Instant::from_secs(11));
+    assert_eq!(correlator.drift_ppm(), 0.0);
+    assert_eq!(correlator.instant(60_000), Some(Instant::from_secs(12)));
+}
+
+#[test]
+fn correlate_over_weeks() {
+    let mut correlator = TimestampCorrelator::new(25_000.0);
+
+    // Sensor clock 100 ppm slow, 25.0025 us per tick, synced every
+    // 1.6e9 ticks for about 15 days
+    const STEP_TICKS: u32 = 1_600_000_000;
+    const STEP_US: u64 = 40_004_000_000;
+    let mut raw = 0u32;
+    let mut at = Instant::from_secs(1);
+    for _ in 0..33 {
+        correlator.sync(raw, at);
+        raw = raw.wrapping_add(STEP_TICKS);
+        at += Duration::from_micros(STEP_US);
+    }
+    let (raw, at) = (
+        raw.wrapping_sub(STEP_TICKS),
+        at - Duration::from_micros(STEP_US),
+    );
+    assert!(at.as_secs() > 13 * 24 * 3600);
+
+    assert!((correlator.drift_ppm() - 100.0).abs() < 0.1);
+    assert_eq!(
+        correlator.instant(raw.wrapping_add(400_000)),
+        Some(at + Duration::from_micros(10_001_000))
+    );
+    assert_eq!(
+        correlator.instant(raw.wrapping_sub(400_000)),
+        Some(at - Duration::from_micros(10_001_000))
+    );
+}
+
+#[test]
+fn sync_timestamp() {
+    let bus = bus(vec![lsm6dso()]);
+    let sensor = Lsm6dso::new(bus, SlaveAddr::Default);
+    let mut correlator = TimestampCorrelator::new(25_000.0);
+    let start = Instant::now();
+
+    with_device(bus, ADDR, |d| {
+        d.regs[0x40..0x44].copy_from_slice(&[0x00, 0x00, 0x01, 0x00])
+    });
+    assert_eq!(block_on(sensor.sync_timestamp(&mut correlator)), Ok(()));
+    assert_eq!(correlator.instant(0x1_0000), Some(start));
+
+    MockDriver::get().advance(Duration::from_secs(2));
+    // 2 s of 25 us ticks later
+    with_device(bus, ADDR, |d| {
+        d.regs[0x40..0x44].copy_from_slice(&(0x1_0000u32 + 80_000).to_le_bytes())
+    });
+    assert_eq!(block_on(sensor.sync_timestamp(&mut correlator)), Ok(()));
+    assert_eq!(correlator.drift_ppm(), 0.0);
+    assert_eq!(
+        correlator.instant(0x1_0000 + 120_000),
+        Some(start + Duration::from_secs(3))
+    );
+}